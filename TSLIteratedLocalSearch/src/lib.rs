@@ -0,0 +1,566 @@
+use std::*;
+
+extern crate tsl_core;
+
+use tsl_core::candidates;
+use tsl_core::candidates::CandidateLists;
+use tsl_core::candidates::DontLookBits;
+use tsl_core::config;
+use tsl_core::config::Configurable;
+use tsl_core::lin_kernighan::LinKernighan;
+use tsl_core::neighbourhood::Move;
+use tsl_core::neighbourhood::Neighbourhood;
+use tsl_core::neighbourhood::Reconnection;
+use tsl_core::observer::Change;
+use tsl_core::observer::Observer;
+use tsl_core::triangular::TriangularMatrix;
+use tsl_core::random_generator::RandomGenerator;
+use tsl_core::solver::Solver;
+use tsl_core::solver::SolverError;
+use tsl_core::tour;
+use tsl_core::trace::TraceRecord;
+
+pub const NUMBER_OF_ITERATIONS: usize = 1000;
+
+// Las perturbaciones necesitan dos ciudades ademas del origen: tres cortes
+// distintos en el doble puente y un tramo de dos ciudades que barajar. Con
+// menos, PathFinder::new rechaza la instancia.
+pub const MIN_CITIES: usize = 3;
+
+// Los mismos valores por defecto que la mejora de Lin-Kernighan por si sola.
+pub const LIN_KERNIGHAN_MAX_DEPTH: usize = 50;
+pub const LIN_KERNIGHAN_BREADTH: usize = 5;
+
+// Perturbacion con la que se sale del optimo local.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Kick {
+    // Doble puente: A B C D pasa a A C B D con tres cortes al azar. Es el
+    // 3-opt que intercambia dos tramos sin invertirlos, que la busqueda local
+    // con 2-opt y Or-opt no sabe deshacer.
+    DoubleBridge,
+    // Baraja las ciudades de un tramo al azar de segment_length ciudades.
+    SegmentShuffle,
+}
+
+impl Kick {
+    pub const NAMES: &'static [&'static str] = &["double-bridge", "segment-shuffle"];
+
+    pub fn from_name(name: &str) -> Option<Kick> {
+        match name {
+            "double-bridge" => Some(Kick::DoubleBridge),
+            "segment-shuffle" => Some(Kick::SegmentShuffle),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Kick::DoubleBridge => "double-bridge",
+            Kick::SegmentShuffle => "segment-shuffle",
+        }
+    }
+}
+
+impl fmt::Display for Kick {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl str::FromStr for Kick {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Kick, String> {
+        Kick::from_name(name).ok_or_else(|| format!("perturbacion desconocida: {}", name))
+    }
+}
+
+// Busqueda local que se aplica tras cada perturbacion.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LocalSearch {
+    // Primera mejora con 2-opt y Or-opt, mirando solo las ciudades de las
+    // aristas que ha cambiado la perturbacion.
+    TwoOptOrOpt,
+    // Cadenas de Lin-Kernighan sobre las mismas listas de candidatos.
+    LinKernighan,
+}
+
+impl LocalSearch {
+    pub const NAMES: &'static [&'static str] = &["two-opt-or-opt", "lin-kernighan"];
+
+    pub fn from_name(name: &str) -> Option<LocalSearch> {
+        match name {
+            "two-opt-or-opt" => Some(LocalSearch::TwoOptOrOpt),
+            "lin-kernighan" => Some(LocalSearch::LinKernighan),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match *self {
+            LocalSearch::TwoOptOrOpt => "two-opt-or-opt",
+            LocalSearch::LinKernighan => "lin-kernighan",
+        }
+    }
+}
+
+impl fmt::Display for LocalSearch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl str::FromStr for LocalSearch {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<LocalSearch, String> {
+        LocalSearch::from_name(name).ok_or_else(|| format!("busqueda local desconocida: {}", name))
+    }
+}
+
+// Criterio para quedarse con el optimo local al que se llega tras perturbar.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Acceptance {
+    // Solo si es mas corto que el actual.
+    Better,
+    // Siempre.
+    RandomWalk,
+    // Como Better, pero tras restart_after perturbaciones sin mejorar la mejor
+    // solucion se vuelve a empezar desde un recorrido al azar.
+    Restart,
+}
+
+impl Acceptance {
+    pub const NAMES: &'static [&'static str] = &["better", "random-walk", "restart"];
+
+    pub fn from_name(name: &str) -> Option<Acceptance> {
+        match name {
+            "better" => Some(Acceptance::Better),
+            "random-walk" => Some(Acceptance::RandomWalk),
+            "restart" => Some(Acceptance::Restart),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Acceptance::Better => "better",
+            Acceptance::RandomWalk => "random-walk",
+            Acceptance::Restart => "restart",
+        }
+    }
+}
+
+impl fmt::Display for Acceptance {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl str::FromStr for Acceptance {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Acceptance, String> {
+        Acceptance::from_name(name).ok_or_else(|| format!("criterio de aceptacion desconocido: {}", name))
+    }
+}
+
+pub struct Parameters {
+    pub candidates: usize,
+    pub local_search: LocalSearch,
+    pub kick: Kick,
+    pub segment_length: usize,
+    pub acceptance: Acceptance,
+    pub restart_after: usize,
+}
+
+impl Default for Parameters {
+    fn default() -> Parameters {
+        Parameters {
+            candidates: 8,
+            local_search: LocalSearch::TwoOptOrOpt,
+            kick: Kick::DoubleBridge,
+            segment_length: 10,
+            acceptance: Acceptance::Better,
+            restart_after: 100,
+        }
+    }
+}
+
+impl Configurable for Parameters {
+    fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+        match key {
+            "candidates" => self.candidates = config::parse_value(key, value)?,
+            "local_search" => self.local_search = config::parse_value(key, value)?,
+            "kick" => self.kick = config::parse_value(key, value)?,
+            "segment_length" => self.segment_length = config::parse_value(key, value)?,
+            "acceptance" => self.acceptance = config::parse_value(key, value)?,
+            "restart_after" => self.restart_after = config::parse_value(key, value)?,
+            _ => return Err(config::unknown_key(key)),
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Parameters {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "\tcandidates = {}", self.candidates)?;
+        writeln!(f, "\tlocal_search = \"{}\"", self.local_search)?;
+        writeln!(f, "\tkick = \"{}\"", self.kick)?;
+        writeln!(f, "\tsegment_length = {}", self.segment_length)?;
+        writeln!(f, "\tacceptance = \"{}\"", self.acceptance)?;
+        writeln!(f, "\trestart_after = {}", self.restart_after)
+    }
+}
+
+pub struct PathFinder<'a> {
+    rand_gen: &'a mut dyn RandomGenerator,
+    cost_map: TriangularMatrix<usize>,
+    parameters: Parameters,
+    solution_size: usize,
+    candidate_lists: CandidateLists,
+    // Posicion de cada ciudad en current_solution y ciudades que quedan por
+    // mirar en la busqueda local.
+    positions: Vec<usize>,
+    dont_look_bits: DontLookBits,
+    lin_kernighan: Option<LinKernighan>,
+    current_solution: Vec<usize>,
+    current_cost: f64,
+    best_solution: Vec<usize>,
+    best_cost: f64,
+    best_solution_iteration: usize,
+    last_kick: Option<Change>,
+    accepted: bool,
+    restarted: bool,
+    non_improvement_iterations: usize,
+    number_of_restarts: usize,
+    total_iterations: usize,
+    evaluations: usize,
+    observers: Vec<Box<dyn Observer<PathFinder<'a>> + 'a>>,
+}
+
+// Una linea por perturbacion con el optimo local al que lleva, y la solucion
+// actual cada vez que cambia.
+pub struct TextTrace;
+
+impl<'a> Observer<PathFinder<'a>> for TextTrace {
+    fn on_start(&mut self, solver: &PathFinder<'a>) {
+        println!("\nSOLUCION S_{} -> {:?}; {}km", solver.total_iterations, solver.current_solution, solver.current_cost);
+    }
+
+    fn on_move_evaluated(&mut self, solver: &PathFinder<'a>, movement: Change, cost: f64) {
+        let kick = match solver.parameters.kick {
+            Kick::DoubleBridge => "DOBLE PUENTE",
+            Kick::SegmentShuffle => "TRAMO BARAJADO",
+        };
+        let positions = match movement {
+            Change::Move(Move::ThreeOpt { i, j, k, .. }) => format!("({}, {}, {})", i, j, k),
+            _ => format!("{:?}", movement.positions()),
+        };
+        println!("\tPERTURBACION {}: {} {}; optimo local {}km", solver.total_iterations, kick, positions, cost);
+    }
+
+    fn on_move_accepted(&mut self, solver: &PathFinder<'a>, _movement: Change, _cost: f64) {
+        println!("\nSOLUCION S_{} -> {:?}; {}km", solver.total_iterations, solver.current_solution, solver.current_cost);
+    }
+
+    fn on_restart(&mut self, solver: &PathFinder<'a>, restart: usize) {
+        println!("\n***************\nREINICIO: {}\n***************", restart);
+        println!("\nSOLUCION S_{} -> {:?}; {}km", solver.total_iterations, solver.current_solution, solver.current_cost);
+    }
+}
+
+impl<'a> PathFinder<'a> {
+    // La busqueda parte del optimo local al que se llega desde la solucion
+    // inicial.
+    pub fn new(cost_map: TriangularMatrix<usize>, rand_gen: &'a mut dyn RandomGenerator,
+               initial_solution: Option<Vec<usize>>, parameters: Parameters) -> Result<PathFinder<'a>, SolverError> {
+        if cost_map.number_of_lines < MIN_CITIES {
+            return Err(SolverError::TooFewCities { needed: MIN_CITIES, found: cost_map.number_of_lines });
+        }
+        let solution_size = cost_map.number_of_lines - 1;
+        let first_solution = match initial_solution {
+            Some(solution) => solution,
+            None => tour::generate_rand_solution(rand_gen, solution_size).ok_or(SolverError::RandomExhausted)?,
+        };
+
+        let mut next_path_finder = PathFinder {
+            rand_gen,
+            candidate_lists: CandidateLists::new(&cost_map, parameters.candidates),
+            lin_kernighan: match parameters.local_search {
+                LocalSearch::TwoOptOrOpt => None,
+                LocalSearch::LinKernighan => Some(LinKernighan::new(&cost_map, parameters.candidates,
+                                                                    LIN_KERNIGHAN_MAX_DEPTH, LIN_KERNIGHAN_BREADTH)),
+            },
+            cost_map,
+            parameters,
+            solution_size,
+            positions: Vec::new(),
+            dont_look_bits: DontLookBits::new(iter::empty(), solution_size + 1),
+            current_solution: Vec::new(),
+            current_cost: 0.0,
+            best_solution: Vec::new(),
+            best_cost: 0.0,
+            best_solution_iteration: 0,
+            last_kick: None,
+            accepted: true,
+            restarted: false,
+            non_improvement_iterations: 0,
+            number_of_restarts: 0,
+            total_iterations: 0,
+            evaluations: 0,
+            observers: Vec::new(),
+        };
+        next_path_finder.start_from(first_solution);
+        next_path_finder.best_solution = next_path_finder.current_solution.clone();
+        next_path_finder.best_cost = next_path_finder.current_cost;
+        Ok(next_path_finder)
+    }
+
+    pub fn add_observer(&mut self, observer: Box<dyn Observer<PathFinder<'a>> + 'a>) {
+        self.observers.push(observer);
+    }
+
+    // Los observadores se sacan mientras se les avisa para que puedan recibir &self.
+    fn notify(&mut self, event: impl Fn(&mut dyn Observer<PathFinder<'a>>, &PathFinder<'a>)) {
+        let mut observers = mem::take(&mut self.observers);
+        for observer in observers.iter_mut() {
+            event(observer.as_mut(), self);
+        }
+        self.observers = observers;
+    }
+
+    // Lleva la solucion a un optimo local mirando todas las ciudades.
+    fn start_from(&mut self, solution: Vec<usize>) {
+        self.current_cost = tour::calculate_cost(&self.cost_map, &solution);
+        self.evaluations += 1;
+        self.positions = candidates::positions(&solution);
+        self.dont_look_bits = DontLookBits::new(tour::to_cycle(&solution), self.solution_size + 1);
+        self.current_solution = solution;
+        self.descend();
+    }
+
+    fn apply(&mut self, movement: Move) {
+        self.current_cost += movement.delta(&self.cost_map, &self.current_solution);
+        self.dont_look_bits.wake_endpoints(&movement, &self.current_solution);
+        movement.apply(&mut self.current_solution);
+        candidates::update_positions(&mut self.positions, &self.current_solution, &movement);
+    }
+
+    // Primera mejora con 2-opt y Or-opt sobre las listas de candidatos, hasta
+    // que no queda ninguna ciudad por mirar. Tras una perturbacion solo se
+    // miran las ciudades de las aristas que ha cambiado. Con Lin-Kernighan se
+    // aplican cadenas hasta el optimo local y las ciudades por mirar se
+    // descartan.
+    fn descend(&mut self) {
+        if let Some(lin_kernighan) = self.lin_kernighan.as_mut() {
+            let evaluations = lin_kernighan.evaluations();
+            lin_kernighan.set_solution(&self.current_solution);
+            self.current_cost -= lin_kernighan.optimise(&self.cost_map);
+            self.evaluations += lin_kernighan.evaluations() - evaluations;
+            self.current_solution = lin_kernighan.solution();
+            self.positions = candidates::positions(&self.current_solution);
+            while self.dont_look_bits.pop().is_some() {}
+            return;
+        }
+        while let Some(node) = self.dont_look_bits.pop() {
+            for neighbourhood in [Neighbourhood::TwoOpt, Neighbourhood::OrOpt] {
+                let moves = self.candidate_lists
+                    .moves_around(neighbourhood, &self.current_solution, &self.positions, node);
+                let improvement = moves.into_iter()
+                    .inspect(|_| self.evaluations += 1)
+                    .find(|movement| movement.delta(&self.cost_map, &self.current_solution) < 0.0);
+                if let Some(movement) = improvement {
+                    self.apply(movement);
+                    break;
+                }
+            }
+        }
+    }
+
+    fn draw(&mut self, limit: usize) -> Option<usize> {
+        Some((limit as f64 * self.rand_gen.next_random()?).floor() as usize)
+    }
+
+    // Aplica la perturbacion y devuelve el cambio: el doble puente entero, que
+    // se puede repetir, o las posiciones entre las que cambia un tramo
+    // barajado. Los aleatorios se sacan antes de cambiar nada: None, con la
+    // solucion intacta, si el generador se agota.
+    fn kick(&mut self) -> Option<Change> {
+        match self.parameters.kick {
+            Kick::DoubleBridge => {
+                // Tres cortes distintos, antes de cualquier posicion o al final.
+                let cuts = self.solution_size + 1;
+                let mut drawn = [self.draw(cuts)?, self.draw(cuts)?, self.draw(cuts)?];
+                for index in 1..drawn.len() {
+                    while drawn[..index].contains(&drawn[index]) {
+                        drawn[index] = (drawn[index] + 1) % cuts;
+                    }
+                }
+                drawn.sort_unstable();
+                let [i, j, k] = drawn;
+                let movement = Move::ThreeOpt { i, j, k, reconnection: Reconnection::Exchange };
+                self.evaluations += 1;
+                self.apply(movement);
+                Some(Change::Move(movement))
+            }
+            Kick::SegmentShuffle => {
+                let length = self.parameters.segment_length.clamp(2, self.solution_size);
+                let start = self.draw(self.solution_size - length + 1)?;
+                let last = start + length - 1;
+                let others = (start + 1..=last).rev()
+                    .map(|position| Some(start + self.draw(position - start + 1)?))
+                    .collect::<Option<Vec<usize>>>()?;
+                if start > 0 {
+                    self.dont_look_bits.wake(self.current_solution[start - 1]);
+                } else {
+                    self.dont_look_bits.wake(0);
+                }
+                self.dont_look_bits.wake(self.current_solution.get(last + 1).cloned().unwrap_or(0));
+                for (position, other) in (start + 1..=last).rev().zip(others) {
+                    self.current_solution.swap(position, other);
+                }
+                for position in start..=last {
+                    let node = self.current_solution[position];
+                    self.positions[node] = position;
+                    self.dont_look_bits.wake(node);
+                }
+                self.current_cost = tour::calculate_cost(&self.cost_map, &self.current_solution);
+                self.evaluations += 1;
+                Some(Change::Span(start, last))
+            }
+        }
+    }
+
+    fn save_current_if_it_is_the_best(&mut self) {
+        if self.current_cost < self.best_cost {
+            self.best_cost = self.current_cost;
+            self.best_solution = self.current_solution.clone();
+            self.best_solution_iteration = self.total_iterations;
+            self.non_improvement_iterations = 0;
+            let cost = self.best_cost;
+            self.notify(|observer, solver| observer.on_new_best(solver, cost));
+        } else {
+            self.non_improvement_iterations += 1;
+        }
+    }
+
+    fn restart_if_necessary(&mut self) {
+        self.restarted = self.parameters.acceptance == Acceptance::Restart
+            && self.non_improvement_iterations >= self.parameters.restart_after;
+        if self.restarted {
+            // Sin aleatorios la iteracion ya hecha se da por buena sin el
+            // reinicio, y la busqueda termina.
+            let Some(solution) = tour::generate_rand_solution(self.rand_gen, self.solution_size) else {
+                self.restarted = false;
+                return;
+            };
+            self.start_from(solution);
+            self.number_of_restarts += 1;
+            let restart = self.number_of_restarts;
+            self.notify(|observer, solver| observer.on_restart(solver, restart));
+            self.save_current_if_it_is_the_best();
+            self.non_improvement_iterations = 0;
+        }
+    }
+
+    fn next_solution(&mut self) {
+        let previous_solution = self.current_solution.clone();
+        let previous_cost = self.current_cost;
+        let Some(kick) = self.kick() else {
+            return;
+        };
+        self.total_iterations += 1;
+        self.descend();
+        self.last_kick = Some(kick);
+        let cost = self.current_cost;
+        self.notify(|observer, solver| observer.on_move_evaluated(solver, kick, cost));
+
+        self.accepted = match self.parameters.acceptance {
+            Acceptance::RandomWalk => true,
+            Acceptance::Better | Acceptance::Restart => self.current_cost < previous_cost,
+        };
+        if self.accepted {
+            self.notify(|observer, solver| observer.on_move_accepted(solver, kick, cost));
+        } else {
+            self.positions = candidates::positions(&previous_solution);
+            self.current_solution = previous_solution;
+            self.current_cost = previous_cost;
+        }
+        self.save_current_if_it_is_the_best();
+        self.restart_if_necessary();
+        self.notify(|observer, solver| observer.on_iteration(solver));
+    }
+
+    pub fn trace_record(&self) -> TraceRecord {
+        TraceRecord {
+            iteration: self.total_iterations,
+            movement: self.last_kick,
+            current_cost: self.current_cost,
+            best_cost: self.best_cost,
+            temperature: None,
+            taboo_list_size: None,
+            accepted: self.accepted,
+            restart: self.restarted,
+        }
+    }
+}
+
+// Cada iteracion es una perturbacion seguida de la busqueda local; la
+// solucion actual depende del criterio de aceptacion y la mejor se guarda
+// aparte.
+impl<'a> Solver for PathFinder<'a> {
+    fn initialise(&mut self) {
+        self.notify(|observer, solver| observer.on_start(solver));
+    }
+
+    fn step(&mut self) {
+        self.next_solution();
+    }
+
+    // Solo termina con los criterios de parada o al agotarse los aleatorios.
+    fn is_finished(&self) -> bool {
+        self.rand_gen.is_exhausted()
+    }
+
+    fn finish(&mut self) {
+        self.notify(|observer, solver| observer.on_finish(solver));
+    }
+
+    fn iteration(&self) -> usize {
+        self.total_iterations
+    }
+
+    fn evaluations(&self) -> usize {
+        self.evaluations
+    }
+
+    fn current_tour(&self) -> &[usize] {
+        &self.current_solution
+    }
+
+    fn current_cost(&self) -> f64 {
+        self.current_cost
+    }
+
+    fn best_tour(&self) -> &[usize] {
+        &self.best_solution
+    }
+
+    fn best_cost(&self) -> f64 {
+        self.best_cost
+    }
+
+    fn summary(&self) -> String {
+        format!("\
+            \nMEJOR SOLUCION: \n\
+            \tRECORRIDO: {}\n\
+            \tCOSTE (km): {}\n\
+            \tITERACION: {}\n\
+            ",
+                tour::format_solution(&self.best_solution),
+                self.best_cost,
+                self.best_solution_iteration
+        )
+    }
+}
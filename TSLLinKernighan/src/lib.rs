@@ -0,0 +1,217 @@
+use std::*;
+
+extern crate tsl_core;
+
+use tsl_core::config;
+use tsl_core::config::Configurable;
+use tsl_core::lin_kernighan::Improvement;
+use tsl_core::lin_kernighan::LinKernighan;
+use tsl_core::observer::Change;
+use tsl_core::observer::Observer;
+use tsl_core::triangular::TriangularMatrix;
+use tsl_core::random_generator::RandomGenerator;
+use tsl_core::solver::Solver;
+use tsl_core::solver::SolverError;
+use tsl_core::tour;
+use tsl_core::trace::TraceRecord;
+
+
+pub struct Parameters {
+    pub neighbours: usize,
+    pub max_depth: usize,
+    pub breadth: usize,
+}
+
+impl Default for Parameters {
+    fn default() -> Parameters {
+        Parameters {
+            neighbours: 10,
+            max_depth: 50,
+            breadth: 5,
+        }
+    }
+}
+
+impl Configurable for Parameters {
+    fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+        match key {
+            "neighbours" => self.neighbours = config::parse_value(key, value)?,
+            "max_depth" => self.max_depth = config::parse_positive(key, value)?,
+            "breadth" => self.breadth = config::parse_value(key, value)?,
+            _ => return Err(config::unknown_key(key)),
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Parameters {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "\tneighbours = {}", self.neighbours)?;
+        writeln!(f, "\tmax_depth = {}", self.max_depth)?;
+        writeln!(f, "\tbreadth = {}", self.breadth)
+    }
+}
+
+pub struct PathFinder<'a> {
+    cost_map: TriangularMatrix<usize>,
+    lin_kernighan: LinKernighan,
+    current_solution: Vec<usize>,
+    current_cost: f64,
+    total_iterations: usize,
+    last_improvement: Option<Improvement>,
+    last_movement: Option<Change>,
+    local_optimum: bool,
+    observers: Vec<Box<dyn Observer<PathFinder<'a>> + 'a>>,
+}
+
+// Una linea por cada cadena aplicada, con el tramo del recorrido que cambia.
+pub struct TextTrace;
+
+impl<'a> Observer<PathFinder<'a>> for TextTrace {
+    fn on_start(&mut self, solver: &PathFinder<'a>) {
+        println!("\nSOLUCION S_{} -> {:?}; {}km", solver.total_iterations, solver.current_solution, solver.current_cost);
+    }
+
+    fn on_move_accepted(&mut self, solver: &PathFinder<'a>, movement: Change, _cost: f64) {
+        let (i, j) = movement.positions();
+        let improvement = solver.last_improvement.expect("mejora sin cadena");
+        println!("\tCADENA DE {} PASOS: posiciones ({}, {}); {}km menos",
+                 improvement.depth, i, j, improvement.gain);
+        println!("\nSOLUCION S_{} -> {:?}; {}km", solver.total_iterations, solver.current_solution, solver.current_cost);
+    }
+}
+
+impl<'a> PathFinder<'a> {
+    pub fn new(cost_map: TriangularMatrix<usize>, rand_gen: &mut dyn RandomGenerator,
+               initial_solution: Option<Vec<usize>>, parameters: Parameters) -> Result<PathFinder<'a>, SolverError> {
+        let solution_size = cost_map.number_of_lines - 1;
+        let first_solution = match initial_solution {
+            Some(solution) => solution,
+            None => tour::generate_rand_solution(rand_gen, solution_size).ok_or(SolverError::RandomExhausted)?,
+        };
+        let mut lin_kernighan = LinKernighan::new(&cost_map, parameters.neighbours, parameters.max_depth,
+                                                  parameters.breadth);
+        lin_kernighan.set_solution(&first_solution);
+
+        Ok(PathFinder {
+            current_cost: tour::calculate_cost(&cost_map, &first_solution),
+            cost_map,
+            lin_kernighan,
+            current_solution: first_solution,
+            total_iterations: 0,
+            last_improvement: None,
+            last_movement: None,
+            local_optimum: false,
+            observers: Vec::new(),
+        })
+    }
+
+    pub fn add_observer(&mut self, observer: Box<dyn Observer<PathFinder<'a>> + 'a>) {
+        self.observers.push(observer);
+    }
+
+    // Los observadores se sacan mientras se les avisa para que puedan recibir &self.
+    fn notify(&mut self, event: impl Fn(&mut dyn Observer<PathFinder<'a>>, &PathFinder<'a>)) {
+        let mut observers = mem::take(&mut self.observers);
+        for observer in observers.iter_mut() {
+            event(observer.as_mut(), self);
+        }
+        self.observers = observers;
+    }
+
+    fn next_solution(&mut self) {
+        self.last_movement = None;
+        self.last_improvement = self.lin_kernighan.improve_step(&self.cost_map);
+        let Some(improvement) = self.last_improvement else {
+            self.local_optimum = true;
+            self.notify(|observer, solver| observer.on_iteration(solver));
+            return;
+        };
+
+        let solution = self.lin_kernighan.solution();
+        let changed = |position: &usize| solution[*position] != self.current_solution[*position];
+        let first = (0..solution.len()).find(changed).unwrap_or(0);
+        let last = (0..solution.len()).rev().find(changed).unwrap_or(0);
+        let movement = Change::Span(first, last);
+
+        self.current_solution = solution;
+        self.current_cost -= improvement.gain;
+        self.total_iterations += 1;
+        self.last_movement = Some(movement);
+        let cost = self.current_cost;
+        self.notify(|observer, solver| observer.on_new_best(solver, cost));
+        self.notify(|observer, solver| observer.on_move_accepted(solver, movement, cost));
+        self.notify(|observer, solver| observer.on_iteration(solver));
+    }
+
+    pub fn trace_record(&self) -> TraceRecord {
+        TraceRecord {
+            iteration: self.total_iterations,
+            movement: self.last_movement,
+            current_cost: self.current_cost,
+            best_cost: self.current_cost,
+            temperature: None,
+            taboo_list_size: None,
+            accepted: self.last_movement.is_some(),
+            restart: false,
+        }
+    }
+}
+
+// Cada iteracion aplica una cadena de Lin-Kernighan que acorta el recorrido; la
+// solucion actual es siempre la mejor.
+impl<'a> Solver for PathFinder<'a> {
+    fn initialise(&mut self) {
+        self.notify(|observer, solver| observer.on_start(solver));
+    }
+
+    fn step(&mut self) {
+        self.next_solution();
+    }
+
+    // Termina en el primer optimo local.
+    fn is_finished(&self) -> bool {
+        self.local_optimum
+    }
+
+    fn finish(&mut self) {
+        self.notify(|observer, solver| observer.on_finish(solver));
+    }
+
+    fn iteration(&self) -> usize {
+        self.total_iterations
+    }
+
+    fn evaluations(&self) -> usize {
+        1 + self.lin_kernighan.evaluations()
+    }
+
+    fn current_tour(&self) -> &[usize] {
+        &self.current_solution
+    }
+
+    fn current_cost(&self) -> f64 {
+        self.current_cost
+    }
+
+    fn best_tour(&self) -> &[usize] {
+        &self.current_solution
+    }
+
+    fn best_cost(&self) -> f64 {
+        self.current_cost
+    }
+
+    fn summary(&self) -> String {
+        format!("\
+            \nMEJOR SOLUCION: \n\
+            \tRECORRIDO: {}\n\
+            \tCOSTE (km): {}\n\
+            \tITERACION: {}\n\
+            ",
+                tour::format_solution(&self.current_solution),
+                self.current_cost,
+                self.total_iterations
+        )
+    }
+}
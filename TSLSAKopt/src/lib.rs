@@ -0,0 +1,451 @@
+use std::*;
+
+extern crate ordered_float;
+extern crate tsl_core;
+
+use ordered_float::OrderedFloat;
+
+use tsl_core::checkpoint::Checkpoint;
+use tsl_core::config;
+use tsl_core::config::ConfigError;
+use tsl_core::neighbourhood::Move;
+use tsl_core::neighbourhood::Neighbourhood;
+use tsl_core::config::Configurable;
+use tsl_core::observer::Change;
+use tsl_core::observer::Observer;
+use tsl_core::triangular::TriangularMatrix;
+use tsl_core::random_generator;
+use tsl_core::random_generator::RandomGenerator;
+use tsl_core::solver::Solver;
+use tsl_core::solver::SolverError;
+use tsl_core::tour;
+use tsl_core::trace::TraceRecord;
+
+pub const NUMBER_OF_ITERATIONS: usize = 10_000;
+
+pub struct Parameters {
+    pub phi: f64,
+    pub mu: f64,
+    pub max_tested_candidates: usize,
+    pub max_accepted_candidates: usize,
+    pub neighbourhood: Neighbourhood,
+}
+
+impl Default for Parameters {
+    fn default() -> Parameters {
+        Parameters {
+            phi: 0.7,
+            mu: 0.01,
+            max_tested_candidates: 120,
+            max_accepted_candidates: 40,
+            neighbourhood: Neighbourhood::TwoOpt,
+        }
+    }
+}
+
+impl Configurable for Parameters {
+    fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+        match key {
+            "phi" => self.phi = config::parse_value(key, value)?,
+            "mu" => self.mu = config::parse_value(key, value)?,
+            "max_tested_candidates" => self.max_tested_candidates = config::parse_value(key, value)?,
+            "max_accepted_candidates" => self.max_accepted_candidates = config::parse_value(key, value)?,
+            "neighbourhood" => self.neighbourhood = config::parse_value(key, value)?,
+            _ => return Err(config::unknown_key(key)),
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Parameters {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "\tphi = {:?}", self.phi)?;
+        writeln!(f, "\tmu = {:?}", self.mu)?;
+        writeln!(f, "\tmax_tested_candidates = {}", self.max_tested_candidates)?;
+        writeln!(f, "\tmax_accepted_candidates = {}", self.max_accepted_candidates)?;
+        writeln!(f, "\tneighbourhood = \"{}\"", self.neighbourhood)
+    }
+}
+
+pub struct PathFinder<'a> {
+    parameters: Parameters,
+    solution_size: usize,
+    rand_gen: &'a mut dyn RandomGenerator,
+    cost_map: TriangularMatrix<usize>,
+
+    current_solution: Vec<usize>,
+    current_solution_cost: f64,
+
+    tested_solution: Vec<usize>,
+    tested_move: Option<Move>,
+    tested_solution_cost: f64,
+
+    best_cost: f64,
+    best_solution: Vec<usize>,
+    best_solution_iteration: usize,
+    last_was_accepted: bool,
+
+    total_iterations: usize,
+    evaluations: usize,
+
+    initial_temperature: f64,
+    current_temperature: f64,
+    accepted_candidates: usize,
+    tested_candidates: usize,
+    delta: f64,
+    cooldowns_counter: usize,
+    // En instancias diminutas puede no haber ningun movimiento.
+    without_moves: bool,
+
+    observers: Vec<Box<dyn Observer<PathFinder<'a>> + 'a>>,
+}
+
+
+impl<'a> fmt::Display for PathFinder<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut acepted_message = "";
+        if self.last_was_accepted {
+            acepted_message = "\tSOLUCION CANDIDATA ACEPTADA\n";
+        }
+
+        if self.cooldowns_counter > 0 && self.tested_candidates == 1 {
+            write!(f, "\
+            ============================\n\
+            ENFRIAMIENTO: {:.6}\n\
+            ============================\n\
+            TEMPERATURA: {:.6}\n\n",
+                   self.cooldowns_counter,
+                   self.current_temperature
+            )?;
+        }
+
+        if self.total_iterations == 0 {
+            write!(f, "\
+            SOLUCION INICIAL:\n\
+            \tRECORRIDO: {}\n\
+            \tFUNCION OBJETIVO (km): {}\n\
+            \tTEMPERATURA INICIAL: {:.6}\n",
+                   tour::format_solution(&self.current_solution),
+                   self.calculate_cost(&self.current_solution),
+                   self.current_temperature
+            )?;
+        } else {
+            writeln!(f, "ITERACION: {}", self.total_iterations)?;
+            // Los 3-opt no caben en un par de posiciones y se escriben enteros.
+            match self.tested_move {
+                Some(movement @ Move::ThreeOpt { .. }) => writeln!(f, "\tMOVIMIENTO: {}", movement)?,
+                _ => {
+                    let (index, insertion) = self.tested_move.map_or((0, 0), |movement| movement.positions());
+                    write!(f, "\
+                    \tINDICE CIUDAD: {}\n\
+                    \tCIUDAD: {}\n\
+                    \tINDICE INSERCION: {}\n",
+                           index,
+                           self.tested_solution[insertion],
+                           insertion
+                    )?
+                }
+            }
+            write!(f, "\
+            \tRECORRIDO: {}\n\
+            \tFUNCION OBJETIVO (km): {}\n\
+            \tDELTA: {:.0}\n\
+            \tTEMPERATURA: {:.6}\n\
+            \tVALOR DE LA EXPONENCIAL: {:.6}\n\
+            {}\
+            \tCANDIDATAS PROBADAS: {}, ACEPTADAS: {}\n\n\
+            ",
+                   tour::format_solution(&self.tested_solution),
+                   self.tested_solution_cost,
+                   self.delta,
+                   self.current_temperature,
+                   f64::exp(-self.delta / self.current_temperature),
+                   acepted_message,
+                   self.tested_candidates, self.accepted_candidates
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+// La traza en castellano de siempre.
+pub struct TextTrace;
+
+impl<'a> Observer<PathFinder<'a>> for TextTrace {
+    fn on_start(&mut self, solver: &PathFinder<'a>) {
+        println!("{}", solver);
+    }
+
+    fn on_iteration(&mut self, solver: &PathFinder<'a>) {
+        print!("{}", solver);
+    }
+}
+
+impl<'a> PathFinder<'a> {
+    pub fn new(cost_map: TriangularMatrix<usize>, rand_gen: &'a mut dyn RandomGenerator,
+               initial_solution: Option<Vec<usize>>, parameters: Parameters) -> Result<PathFinder<'a>, SolverError> {
+        let solution_size = cost_map.number_of_lines - 1;
+
+        let first_solution = match initial_solution {
+            Some(solution) => solution,
+            None => tour::generate_rand_solution(rand_gen, solution_size).ok_or(SolverError::RandomExhausted)?,
+        };
+
+        let mut next_pf = PathFinder {
+            parameters,
+            rand_gen,
+            cost_map,
+            current_solution: first_solution,
+            current_solution_cost: 0.0,
+            best_solution: Vec::new(),
+            best_solution_iteration: 0,
+            solution_size,
+            last_was_accepted: true,
+            tested_move: None,
+            tested_solution: Vec::new(),
+            tested_solution_cost: 0.0,
+            best_cost: 0.0,
+            total_iterations: 0,
+            evaluations: 1,
+            accepted_candidates: 0,
+            tested_candidates: 0,
+            current_temperature: 0.0,
+            cooldowns_counter: 0,
+            delta: 0.0,
+            initial_temperature: 0.0,
+            without_moves: false,
+            observers: Vec::new(),
+        };
+        next_pf.best_cost = next_pf.calculate_cost(&next_pf.current_solution);
+        next_pf.best_solution = next_pf.current_solution.clone();
+        next_pf.current_solution_cost = next_pf.best_cost;
+        next_pf.current_temperature = (-next_pf.parameters.mu / f64::ln(next_pf.parameters.phi)) * next_pf.best_cost;
+        next_pf.initial_temperature = next_pf.current_temperature;
+        Ok(next_pf)
+    }
+
+
+    // Continua la busqueda desde un punto de control guardado con save_state.
+    pub fn resume(cost_map: TriangularMatrix<usize>, rand_gen: &'a mut dyn RandomGenerator,
+                  checkpoint: &Checkpoint, parameters: Parameters) -> Result<PathFinder<'a>, ConfigError> {
+        let solution_size = cost_map.number_of_lines - 1;
+        let current_solution = checkpoint.get_tour("current_solution", solution_size)?;
+        let mut path_finder = PathFinder::new(cost_map, rand_gen, Some(current_solution), parameters)
+            .map_err(|error| checkpoint.error("current_solution", error.to_string()))?;
+        path_finder.current_solution_cost = checkpoint.get("current_solution_cost")?;
+        path_finder.tested_solution = checkpoint.get_list("tested_solution")?;
+        path_finder.tested_solution_cost = checkpoint.get("tested_solution_cost")?;
+        path_finder.best_solution = checkpoint.get_tour("best_solution", solution_size)?;
+        path_finder.best_cost = checkpoint.get("best_cost")?;
+        path_finder.best_solution_iteration = checkpoint.get("best_solution_iteration")?;
+        path_finder.last_was_accepted = checkpoint.get("last_was_accepted")?;
+        path_finder.total_iterations = checkpoint.get("total_iterations")?;
+        if path_finder.total_iterations > 0 {
+            path_finder.tested_move = Some(checkpoint.get_move("tested_move", solution_size)?);
+        }
+        path_finder.evaluations = checkpoint.get("evaluations")?;
+        path_finder.initial_temperature = checkpoint.get("initial_temperature")?;
+        path_finder.current_temperature = checkpoint.get("current_temperature")?;
+        path_finder.accepted_candidates = checkpoint.get("accepted_candidates")?;
+        path_finder.tested_candidates = checkpoint.get("tested_candidates")?;
+        path_finder.delta = checkpoint.get("delta")?;
+        path_finder.cooldowns_counter = checkpoint.get("cooldowns_counter")?;
+        checkpoint.restore_random(path_finder.rand_gen)?;
+        Ok(path_finder)
+    }
+
+    pub fn save_state(&self, checkpoint: &mut Checkpoint) {
+        checkpoint.set_list("current_solution", &self.current_solution);
+        checkpoint.set("current_solution_cost", self.current_solution_cost);
+        checkpoint.set_list("tested_solution", &self.tested_solution);
+        if let Some(movement) = self.tested_move {
+            checkpoint.set("tested_move", movement.code());
+        }
+        checkpoint.set("tested_solution_cost", self.tested_solution_cost);
+        checkpoint.set_list("best_solution", &self.best_solution);
+        checkpoint.set("best_cost", self.best_cost);
+        checkpoint.set("best_solution_iteration", self.best_solution_iteration);
+        checkpoint.set("last_was_accepted", self.last_was_accepted);
+        checkpoint.set("total_iterations", self.total_iterations);
+        checkpoint.set("evaluations", self.evaluations);
+        checkpoint.set("initial_temperature", self.initial_temperature);
+        checkpoint.set("current_temperature", self.current_temperature);
+        checkpoint.set("accepted_candidates", self.accepted_candidates);
+        checkpoint.set("tested_candidates", self.tested_candidates);
+        checkpoint.set("delta", self.delta);
+        checkpoint.set("cooldowns_counter", self.cooldowns_counter);
+        checkpoint.set_random(self.rand_gen);
+        checkpoint.set_parameters(&self.parameters);
+    }
+
+    pub fn add_observer(&mut self, observer: Box<dyn Observer<PathFinder<'a>> + 'a>) {
+        self.observers.push(observer);
+    }
+
+    // Los observadores se sacan mientras se les avisa para que puedan recibir &self.
+    fn notify(&mut self, event: impl Fn(&mut dyn Observer<PathFinder<'a>>, &PathFinder<'a>)) {
+        let mut observers = mem::take(&mut self.observers);
+        for observer in observers.iter_mut() {
+            event(observer.as_mut(), self);
+        }
+        self.observers = observers;
+    }
+
+    pub fn calculate_cost(&self, solution: &[usize]) -> f64 {
+        tour::calculate_cost(&self.cost_map, solution)
+    }
+
+    // Los movimientos que parten de una posicion elegida al azar y el aleatorio
+    // con el que se decide si se acepta el mejor. None si el generador se agota.
+    fn generate_neighbours(&mut self) -> Option<(Vec<Move>, f64)> {
+        let neighbourhood = self.parameters.neighbourhood;
+        let solution_size = self.solution_size;
+        random_generator::draw_all(self.rand_gen, |rand_gen| {
+            Some((neighbourhood.sample(solution_size, rand_gen)?, rand_gen.next_random()?))
+        })
+    }
+
+    fn save_tested_if_proceed(&mut self, tested_move: Move, acceptance_random: f64) {
+        let (movement, tested_cost) = (Change::Move(tested_move), self.tested_solution_cost);
+        self.notify(|observer, solver| observer.on_move_evaluated(solver, movement, tested_cost));
+        self.delta = self.tested_solution_cost - self.current_solution_cost;
+
+        let probability_of_acceptation = if self.tested_solution_cost < self.current_solution_cost {
+            1.0
+        } else {
+            f64::exp(-self.delta / self.current_temperature)
+        };
+
+        self.last_was_accepted = acceptance_random < probability_of_acceptation;
+
+        if self.last_was_accepted {
+            self.current_solution = self.tested_solution.clone();
+            self.current_solution_cost = self.tested_solution_cost;
+            self.accepted_candidates += 1;
+        }
+        self.tested_candidates += 1;
+
+        if self.current_solution_cost < self.best_cost {
+            self.best_cost = self.current_solution_cost;
+            self.best_solution = self.current_solution.clone();
+            self.best_solution_iteration = self.total_iterations;
+            self.notify(|observer, solver| observer.on_new_best(solver, tested_cost));
+        }
+        if self.last_was_accepted {
+            self.notify(|observer, solver| observer.on_move_accepted(solver, movement, tested_cost));
+        }
+    }
+
+    fn cooldown_if_proceed(&mut self) {
+        if self.tested_candidates >= self.parameters.max_tested_candidates
+            || self.accepted_candidates >= self.parameters.max_accepted_candidates {
+            self.accepted_candidates = 0;
+            self.tested_candidates = 0;
+            self.cooldowns_counter += 1;
+            self.current_temperature = self.initial_temperature / (1.0 + self.cooldowns_counter as f64);
+            let (cooldown, temperature) = (self.cooldowns_counter, self.current_temperature);
+            self.notify(|observer, solver| observer.on_cooldown(solver, cooldown, temperature));
+        }
+    }
+
+    // Los aleatorios se sacan antes de tocar nada: si se agotan, la busqueda
+    // se queda como estaba al final de la iteracion anterior.
+    fn next_solution(&mut self) {
+        let Some((neighbours, acceptance_random)) = self.generate_neighbours() else {
+            return;
+        };
+        let Some((best_move, best_neighbour_cost)) = neighbours.iter()
+            .map(|movement| (*movement, self.current_solution_cost + movement.delta(&self.cost_map, &self.current_solution)))
+            .min_by_key(|&(_, cost)| OrderedFloat(cost)) else {
+            self.without_moves = true;
+            return;
+        };
+        self.cooldown_if_proceed();
+
+        self.evaluations += neighbours.len();
+        self.tested_solution_cost = best_neighbour_cost;
+        self.tested_move = Some(best_move);
+        self.tested_solution = best_move.applied_to(&self.current_solution);
+
+        self.total_iterations += 1;
+        self.save_tested_if_proceed(best_move, acceptance_random);
+        self.notify(|observer, solver| observer.on_iteration(solver));
+    }
+
+    pub fn trace_record(&self) -> TraceRecord {
+        let started = self.total_iterations > 0;
+        TraceRecord {
+            iteration: self.total_iterations,
+            movement: if started { self.tested_move.map(Change::Move) } else { None },
+            current_cost: self.current_solution_cost,
+            best_cost: self.best_cost,
+            temperature: Some(self.current_temperature),
+            taboo_list_size: None,
+            accepted: started && self.last_was_accepted,
+            restart: false,
+        }
+    }
+}
+
+impl<'a> Solver for PathFinder<'a> {
+    fn initialise(&mut self) {
+        self.notify(|observer, solver| observer.on_start(solver));
+    }
+
+    fn step(&mut self) {
+        self.next_solution();
+    }
+
+    // Solo se detiene por los criterios de parada, salvo que no haya ningun
+    // movimiento posible o se agoten los aleatorios.
+    fn is_finished(&self) -> bool {
+        self.without_moves || self.rand_gen.is_exhausted()
+    }
+
+    fn finish(&mut self) {
+        self.notify(|observer, solver| observer.on_finish(solver));
+    }
+
+    fn iteration(&self) -> usize {
+        self.total_iterations
+    }
+
+    fn evaluations(&self) -> usize {
+        self.evaluations
+    }
+
+    fn temperature(&self) -> Option<f64> {
+        Some(self.current_temperature)
+    }
+
+    fn current_tour(&self) -> &[usize] {
+        &self.current_solution
+    }
+
+    fn current_cost(&self) -> f64 {
+        self.current_solution_cost
+    }
+
+    fn best_tour(&self) -> &[usize] {
+        &self.best_solution
+    }
+
+    fn best_cost(&self) -> f64 {
+        self.best_cost
+    }
+
+    fn summary(&self) -> String {
+        format!("\
+            \nMEJOR SOLUCION: \n\
+            \tRECORRIDO: {}\n\
+            \tFUNCION OBJETIVO (km): {}\n\
+            \tITERACION: {}\n\
+            \tmu = {:#?}, phi = {:#?}\n",
+                tour::format_solution(&self.best_solution),
+                self.best_cost,
+                self.best_solution_iteration,
+                self.parameters.mu, self.parameters.phi
+        )
+    }
+}
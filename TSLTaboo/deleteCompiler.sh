@@ -1,3 +0,0 @@
-#!/bin/bash
-source $HOME/.cargo/env
-rustup self uninstall -y
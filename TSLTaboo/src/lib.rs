@@ -0,0 +1,411 @@
+use std::*;
+
+extern crate ordered_float;
+extern crate linked_hash_set;
+extern crate tsl_core;
+
+use linked_hash_set::LinkedHashSet;
+use ordered_float::OrderedFloat;
+
+use tsl_core::candidates;
+use tsl_core::candidates::CandidateLists;
+use tsl_core::checkpoint::Checkpoint;
+use tsl_core::config;
+use tsl_core::config::ConfigError;
+use tsl_core::neighbourhood::Move;
+use tsl_core::neighbourhood::Neighbourhood;
+use tsl_core::config::Configurable;
+use tsl_core::observer::Change;
+use tsl_core::observer::Observer;
+use tsl_core::triangular::TriangularMatrix;
+use tsl_core::random_generator::RandomGenerator;
+use tsl_core::solver::Solver;
+use tsl_core::solver::SolverError;
+use tsl_core::tour;
+use tsl_core::trace::TraceRecord;
+
+pub const NUMBER_OF_ITERATIONS: usize = 10_000;
+
+pub struct Parameters {
+    pub taboo_list_max_elements: usize,
+    pub reboot_on_it: usize,
+    pub neighbourhood: Neighbourhood,
+    // Tamano de las listas de candidatos; con 0 se recorre el vecindario entero.
+    pub candidates: usize,
+}
+
+impl Default for Parameters {
+    fn default() -> Parameters {
+        Parameters {
+            taboo_list_max_elements: 100,
+            reboot_on_it: 99,
+            neighbourhood: Neighbourhood::Swap,
+            candidates: 0,
+        }
+    }
+}
+
+impl Configurable for Parameters {
+    fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+        match key {
+            "taboo_list_max_elements" => self.taboo_list_max_elements = config::parse_positive(key, value)?,
+            "reboot_on_it" => self.reboot_on_it = config::parse_value(key, value)?,
+            "neighbourhood" => self.neighbourhood = config::parse_value(key, value)?,
+            "candidates" => self.candidates = config::parse_value(key, value)?,
+            _ => return Err(config::unknown_key(key)),
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Parameters {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "\ttaboo_list_max_elements = {}", self.taboo_list_max_elements)?;
+        writeln!(f, "\treboot_on_it = {}", self.reboot_on_it)?;
+        writeln!(f, "\tneighbourhood = \"{}\"", self.neighbourhood)?;
+        writeln!(f, "\tcandidates = {}", self.candidates)
+    }
+}
+
+pub struct PathFinder {
+    cost_map: TriangularMatrix<usize>,
+    current_solution: Vec<usize>,
+    best_cost: f64,
+    best_solution: Vec<usize>,
+    best_solution_iteration: usize,
+    taboo_list: LinkedHashSet<(usize, usize)>,
+    candidate_lists: Option<CandidateLists>,
+    parameters: Parameters,
+    non_improvement_iterations: usize,
+    total_iterations: usize,
+    evaluations: usize,
+    number_of_reboots: usize,
+    rebooted: bool,
+    last_move: Option<Move>,
+    // En instancias diminutas puede no haber ningun movimiento.
+    without_moves: bool,
+    observers: Vec<Box<dyn Observer<PathFinder>>>,
+}
+
+
+impl fmt::Display for PathFinder {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.total_iterations == 0 {
+            write!(f, "\
+            RECORRIDO INICIAL\n\
+            \tRECORRIDO: {}\n\
+            \tCOSTE (km): {}\n\
+            ",
+                   tour::format_solution(&self.current_solution),
+                   self.calculate_cost(&self.current_solution)
+            )?;
+        } else {
+            write!(f, "\
+            ITERACION: {}\n\
+            \tINTERCAMBIO: {:?}\n\
+            \tRECORRIDO: {}\n\
+            \tCOSTE (km): {}\n\
+            \tITERACIONES SIN MEJORA: {}\n\
+            \tLISTA TABU:\n{}\n\
+            ",
+                   self.total_iterations,
+                   self.taboo_list.back().unwrap(),
+                   tour::format_solution(&self.current_solution),
+                   self.calculate_cost(&self.current_solution),
+                   self.non_improvement_iterations,
+                   self.taboo_list.iter()
+                       .fold(String::new(), |acc, &(i, j)| {
+                           acc + "\t" + &i.to_string() + " " + &j.to_string() + "\n"
+                       })
+            )?;
+        }
+
+        if self.non_improvement_iterations > self.parameters.reboot_on_it {
+            write!(f, "\
+                ***************\n\
+                REINICIO: {}\n\
+                ***************\n\n",
+                   self.number_of_reboots
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+// La traza en castellano de siempre.
+pub struct TextTrace;
+
+impl Observer<PathFinder> for TextTrace {
+    fn on_start(&mut self, solver: &PathFinder) {
+        println!("{}", solver);
+    }
+
+    fn on_iteration(&mut self, solver: &PathFinder) {
+        print!("{}", solver);
+    }
+}
+
+impl PathFinder {
+    pub fn new(cost_map: TriangularMatrix<usize>, rand_gen: &mut dyn RandomGenerator,
+               initial_solution: Option<Vec<usize>>, parameters: Parameters)
+               -> Result<PathFinder, SolverError> {
+        let solution_size = cost_map.number_of_lines - 1;
+        let taboo_list = LinkedHashSet::new();
+        let first_solution = match initial_solution {
+            Some(solution) => solution,
+            None => tour::generate_rand_solution(rand_gen, solution_size).ok_or(SolverError::RandomExhausted)?,
+        };
+
+        let candidate_lists = match parameters.candidates {
+            0 => None,
+            size => Some(CandidateLists::new(&cost_map, size)),
+        };
+
+        let mut next_path_finder = PathFinder {
+            cost_map,
+            current_solution: first_solution,
+            best_solution: Vec::new(),
+            best_solution_iteration: 0,
+            best_cost: 0.0,
+            taboo_list,
+            candidate_lists,
+            parameters,
+            non_improvement_iterations: 0,
+            total_iterations: 0,
+            evaluations: 1,
+            number_of_reboots: 1,
+            rebooted: false,
+            last_move: None,
+            without_moves: false,
+            observers: Vec::new(),
+        };
+        next_path_finder.best_cost = next_path_finder.calculate_cost(&next_path_finder.current_solution);
+        next_path_finder.best_solution = next_path_finder.current_solution.clone();
+        Ok(next_path_finder)
+    }
+
+    // Continua la busqueda desde un punto de control guardado con save_state.
+    pub fn resume(cost_map: TriangularMatrix<usize>, rand_gen: &mut dyn RandomGenerator,
+                  checkpoint: &Checkpoint, parameters: Parameters) -> Result<PathFinder, ConfigError> {
+        let solution_size = cost_map.number_of_lines - 1;
+        let current_solution = checkpoint.get_tour("current_solution", solution_size)?;
+        let mut path_finder = PathFinder::new(cost_map, rand_gen, Some(current_solution), parameters)
+            .map_err(|error| checkpoint.error("current_solution", error.to_string()))?;
+        path_finder.best_solution = checkpoint.get_tour("best_solution", solution_size)?;
+        path_finder.best_cost = checkpoint.get("best_cost")?;
+        path_finder.best_solution_iteration = checkpoint.get("best_solution_iteration")?;
+        path_finder.taboo_list = checkpoint.get_pairs("taboo_list")?.into_iter().collect();
+        path_finder.non_improvement_iterations = checkpoint.get("non_improvement_iterations")?;
+        path_finder.total_iterations = checkpoint.get("total_iterations")?;
+        path_finder.evaluations = checkpoint.get("evaluations")?;
+        path_finder.number_of_reboots = checkpoint.get("number_of_reboots")?;
+        path_finder.rebooted = checkpoint.get("rebooted")?;
+        if path_finder.total_iterations > 0 {
+            path_finder.last_move = Some(checkpoint.get_move("last_move", solution_size)?);
+        }
+        Ok(path_finder)
+    }
+
+    pub fn save_state(&self, checkpoint: &mut Checkpoint) {
+        checkpoint.set_list("current_solution", &self.current_solution);
+        checkpoint.set_list("best_solution", &self.best_solution);
+        checkpoint.set("best_cost", self.best_cost);
+        checkpoint.set("best_solution_iteration", self.best_solution_iteration);
+        checkpoint.set_pairs("taboo_list", self.taboo_list.iter().cloned());
+        checkpoint.set("non_improvement_iterations", self.non_improvement_iterations);
+        checkpoint.set("total_iterations", self.total_iterations);
+        checkpoint.set("evaluations", self.evaluations);
+        checkpoint.set("number_of_reboots", self.number_of_reboots);
+        checkpoint.set("rebooted", self.rebooted);
+        if let Some(movement) = self.last_move {
+            checkpoint.set("last_move", movement.code());
+        }
+        checkpoint.set_parameters(&self.parameters);
+    }
+
+    pub fn add_observer(&mut self, observer: Box<dyn Observer<PathFinder>>) {
+        self.observers.push(observer);
+    }
+
+    // Los observadores se sacan mientras se les avisa para que puedan recibir &self.
+    fn notify(&mut self, event: impl Fn(&mut dyn Observer<PathFinder>, &PathFinder)) {
+        let mut observers = mem::take(&mut self.observers);
+        for observer in observers.iter_mut() {
+            event(observer.as_mut(), self);
+        }
+        self.observers = observers;
+    }
+
+    pub fn calculate_cost(&self, solution: &[usize]) -> f64 {
+        tour::calculate_cost(&self.cost_map, solution)
+    }
+
+    fn reboot_if_necessary(&mut self) {
+        self.rebooted = self.non_improvement_iterations > self.parameters.reboot_on_it;
+        if self.rebooted {
+            self.current_solution = self.best_solution.clone();
+            self.non_improvement_iterations = 0;
+            self.taboo_list.clear();
+            self.number_of_reboots += 1;
+            let restart = self.number_of_reboots - 1;
+            self.notify(|observer, solver| observer.on_restart(solver, restart));
+        }
+    }
+
+    fn save_current_if_it_is_the_best(&mut self, current_solution_cost: f64) {
+        if current_solution_cost < self.best_cost {
+            self.best_cost = current_solution_cost;
+            self.best_solution = self.current_solution.clone();
+            self.non_improvement_iterations = 0;
+            self.best_solution_iteration = self.total_iterations;
+            self.notify(|observer, solver| observer.on_new_best(solver, current_solution_cost));
+        } else {
+            self.non_improvement_iterations += 1;
+        }
+    }
+
+    fn update_taboo_list(&mut self, swap: (usize, usize)){
+        if self.taboo_list.len() == self.parameters.taboo_list_max_elements {
+            self.taboo_list.pop_front();
+        }
+        self.taboo_list.insert(swap);
+    }
+
+    // Con listas de candidatos, solo los movimientos que acercan una ciudad a
+    // una de sus candidatas.
+    fn moves<'b>(&'b self, positions: &'b [usize]) -> Box<dyn Iterator<Item = Move> + 'b> {
+        let neighbourhood = self.parameters.neighbourhood;
+        match self.candidate_lists {
+            Some(ref candidate_lists) => Box::new(candidate_lists.moves(neighbourhood, &self.current_solution, positions)),
+            None => Box::new(neighbourhood.moves(self.current_solution.len())),
+        }
+    }
+
+    // El mejor vecino de los movimientos que no son tabu y el numero de
+    // movimientos evaluados.
+    fn best_neighbour(&self, positions: &[usize], current_cost: f64) -> (Option<(Move, f64)>, usize) {
+        let mut candidates = 0;
+        let best = self.moves(positions)
+            .filter(|movement| !self.taboo_list.contains(&movement.positions())
+                && !self.taboo_list.contains(&movement.undo_positions()))
+            .inspect(|_| candidates += 1)
+            .map(|movement| (movement, current_cost + movement.delta(&self.cost_map, &self.current_solution)))
+            .min_by_key(|&(_, cost)| OrderedFloat(cost));
+        (best, candidates)
+    }
+
+    // Aspiracion por defecto: si todos los movimientos son tabu se aplica el
+    // que lleva mas tiempo prohibido, y entre esos el mejor. Con el mejor sin
+    // mas se desharia una y otra vez el ultimo movimiento.
+    fn least_taboo_neighbour(&self, positions: &[usize], current_cost: f64) -> (Option<(Move, f64)>, usize) {
+        let ages: collections::HashMap<(usize, usize), usize> = self.taboo_list.iter().enumerate()
+            .map(|(age, &pair)| (pair, age))
+            .collect();
+        let age = |movement: &Move| cmp::max(ages.get(&movement.positions()), ages.get(&movement.undo_positions()));
+        let mut candidates = 0;
+        let best = self.moves(positions)
+            .inspect(|_| candidates += 1)
+            .map(|movement| (movement, current_cost + movement.delta(&self.cost_map, &self.current_solution)))
+            .min_by_key(|&(movement, cost)| (age(&movement), OrderedFloat(cost)));
+        (best, candidates)
+    }
+
+    fn next_solution(&mut self) {
+        self.reboot_if_necessary();
+        let current_cost = self.calculate_cost(&self.current_solution);
+        let positions = match self.candidate_lists {
+            Some(_) => candidates::positions(&self.current_solution),
+            None => Vec::new(),
+        };
+        let (best, candidates) = match self.best_neighbour(&positions, current_cost) {
+            (None, _) => self.least_taboo_neighbour(&positions, current_cost),
+            allowed => allowed,
+        };
+        self.evaluations += candidates;
+        let Some((best_move, best_neighbour_cost)) = best else {
+            self.without_moves = true;
+            return;
+        };
+
+        self.notify(|observer, solver| observer.on_move_evaluated(solver, Change::Move(best_move), best_neighbour_cost));
+        best_move.apply(&mut self.current_solution);
+
+        self.total_iterations += 1;
+        self.update_taboo_list(best_move.positions());
+        self.last_move = Some(best_move);
+        self.save_current_if_it_is_the_best(best_neighbour_cost);
+        self.notify(|observer, solver| observer.on_move_accepted(solver, Change::Move(best_move), best_neighbour_cost));
+        self.notify(|observer, solver| observer.on_iteration(solver));
+    }
+
+    pub fn trace_record(&self) -> TraceRecord {
+        TraceRecord {
+            iteration: self.total_iterations,
+            movement: self.last_move.map(Change::Move),
+            current_cost: self.calculate_cost(&self.current_solution),
+            best_cost: self.best_cost,
+            temperature: None,
+            taboo_list_size: Some(self.taboo_list.len()),
+            accepted: self.total_iterations > 0,
+            restart: self.rebooted,
+        }
+    }
+}
+
+impl Solver for PathFinder {
+    fn initialise(&mut self) {
+        self.notify(|observer, solver| observer.on_start(solver));
+    }
+
+    fn step(&mut self) {
+        self.next_solution();
+    }
+
+    // Se detiene por el numero de iteraciones, salvo que no haya ningun
+    // movimiento posible.
+    fn is_finished(&self) -> bool {
+        self.without_moves
+    }
+
+    fn finish(&mut self) {
+        self.notify(|observer, solver| observer.on_finish(solver));
+    }
+
+    fn iteration(&self) -> usize {
+        self.total_iterations
+    }
+
+    fn evaluations(&self) -> usize {
+        self.evaluations
+    }
+
+    fn current_tour(&self) -> &[usize] {
+        &self.current_solution
+    }
+
+    fn current_cost(&self) -> f64 {
+        self.calculate_cost(&self.current_solution)
+    }
+
+    fn best_tour(&self) -> &[usize] {
+        &self.best_solution
+    }
+
+    fn best_cost(&self) -> f64 {
+        self.best_cost
+    }
+
+    fn summary(&self) -> String {
+        format!("\
+            \nMEJOR SOLUCION: \n\
+            \tRECORRIDO: {}\n\
+            \tCOSTE (km): {}\n\
+            \tITERACION: {}\n\
+            ",
+                tour::format_solution(&self.best_solution),
+                self.calculate_cost(&self.best_solution),
+                self.best_solution_iteration
+        )
+    }
+}
@@ -0,0 +1,540 @@
+use std::*;
+
+extern crate ordered_float;
+extern crate linked_hash_set;
+extern crate rayon;
+extern crate tsl_core;
+
+use linked_hash_set::LinkedHashSet;
+use ordered_float::OrderedFloat;
+use rayon::prelude::*;
+
+use tsl_core::candidates;
+use tsl_core::candidates::CandidateLists;
+use tsl_core::checkpoint::Checkpoint;
+use tsl_core::config;
+use tsl_core::config::ConfigError;
+use tsl_core::neighbourhood::Move;
+use tsl_core::neighbourhood::Neighbourhood;
+use tsl_core::config::Configurable;
+use tsl_core::observer::Change;
+use tsl_core::observer::Observer;
+use tsl_core::triangular::TriangularMatrix;
+use tsl_core::triangular::FreqMatrix;
+use tsl_core::random_generator;
+use tsl_core::random_generator::RandomGenerator;
+use tsl_core::solver::Solver;
+use tsl_core::tour;
+use tsl_core::trace::TraceRecord;
+
+pub const NUMBER_OF_ITERATIONS: usize = 10_000;
+
+pub struct Parameters {
+    pub taboo_list_max_elements: usize,
+    pub reboot_on_it: usize,
+    pub divisor_change_on_reboot: usize,
+    pub tries_on_reboot: usize,
+    pub repetition_const: f64,
+    pub intensification_mod: usize,
+    pub neighbourhood: Neighbourhood,
+    // Tamano de las listas de candidatos; con 0 se recorre el vecindario entero.
+    pub candidates: usize,
+}
+
+impl Default for Parameters {
+    fn default() -> Parameters {
+        Parameters {
+            taboo_list_max_elements: 30,
+            reboot_on_it: 99,
+            divisor_change_on_reboot: 4,
+            tries_on_reboot: 1000,
+            repetition_const: 1.0,
+            intensification_mod: 10,
+            neighbourhood: Neighbourhood::TwoOpt,
+            candidates: 0,
+        }
+    }
+}
+
+impl Configurable for Parameters {
+    fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+        match key {
+            "taboo_list_max_elements" => self.taboo_list_max_elements = config::parse_positive(key, value)?,
+            "reboot_on_it" => self.reboot_on_it = config::parse_value(key, value)?,
+            "divisor_change_on_reboot" => self.divisor_change_on_reboot = config::parse_positive(key, value)?,
+            "tries_on_reboot" => self.tries_on_reboot = config::parse_positive(key, value)?,
+            "repetition_const" => self.repetition_const = config::parse_value(key, value)?,
+            "intensification_mod" => self.intensification_mod = config::parse_value(key, value)?,
+            "neighbourhood" => self.neighbourhood = config::parse_value(key, value)?,
+            "candidates" => self.candidates = config::parse_value(key, value)?,
+            _ => return Err(config::unknown_key(key)),
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Parameters {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "\ttaboo_list_max_elements = {}", self.taboo_list_max_elements)?;
+        writeln!(f, "\treboot_on_it = {}", self.reboot_on_it)?;
+        writeln!(f, "\tdivisor_change_on_reboot = {}", self.divisor_change_on_reboot)?;
+        writeln!(f, "\ttries_on_reboot = {}", self.tries_on_reboot)?;
+        writeln!(f, "\trepetition_const = {:?}", self.repetition_const)?;
+        writeln!(f, "\tintensification_mod = {}", self.intensification_mod)?;
+        writeln!(f, "\tneighbourhood = \"{}\"", self.neighbourhood)?;
+        writeln!(f, "\tcandidates = {}", self.candidates)
+    }
+}
+
+pub struct PathFinder<'a> {
+    rand_gen: &'a mut dyn RandomGenerator,
+    cost_map: TriangularMatrix<usize>,
+    current_solution: Vec<usize>,
+    solution_size: usize,
+    best_cost: f64,
+    best_solution: Vec<usize>,
+    best_solution_iteration: usize,
+    taboo_list: LinkedHashSet<(usize, usize)>,
+    non_improvement_iterations: usize,
+    total_iterations: usize,
+    evaluations: usize,
+    number_of_reboots: usize,
+    rebooted: bool,
+    last_move: Option<Move>,
+    // En instancias diminutas puede no haber ningun movimiento.
+    without_moves: bool,
+    freq_mat: FreqMatrix,
+    candidate_lists: Option<CandidateLists>,
+    parameters: Parameters,
+    observers: Vec<Box<dyn Observer<PathFinder<'a>> + 'a>>,
+}
+
+
+// El mejor de dos resultados parciales, el primero si cuestan lo mismo, con la
+// suma de los movimientos evaluados.
+fn first_best((first, first_candidates): (Option<(Move, f64)>, usize),
+              (second, second_candidates): (Option<(Move, f64)>, usize)) -> (Option<(Move, f64)>, usize) {
+    let best = match (first, second) {
+        (Some(first), Some(second)) if second.1 < first.1 => Some(second),
+        (None, second) => second,
+        (first, _) => first,
+    };
+    (best, first_candidates + second_candidates)
+}
+
+impl<'a> fmt::Display for PathFinder<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.total_iterations == 0 {
+            write!(f, "\
+            RECORRIDO INICIAL\n\
+            \tRECORRIDO: {}\n\
+            \tCOSTE (km): {}\n\
+            ",
+                   tour::format_solution(&self.current_solution),
+                   self.calculate_cost(&self.current_solution)
+            )?;
+        } else {
+            write!(f, "\
+            ITERACION: {}\n\
+            \tINTERCAMBIO: {:?}\n\
+            \tRECORRIDO: {}\n\
+            \tCOSTE (km): {}\n\
+            \tITERACIONES SIN MEJORA: {}\n\
+            \tLISTA TABU:\n{}\n\
+            ",
+                   self.total_iterations,
+                   self.taboo_list.back().unwrap(),
+                   tour::format_solution(&self.current_solution),
+                   self.calculate_cost(&self.current_solution),
+                   self.non_improvement_iterations,
+                   self.taboo_list.iter()
+                       .fold(String::new(), |acc, &(i, j)| {
+                           acc + "\t" + &i.to_string() + " " + &j.to_string() + "\n"
+                       })
+            )?;
+        }
+
+        if self.non_improvement_iterations > self.parameters.reboot_on_it {
+            write!(f, "\
+                ***************\n\
+                REINICIO: {}\n\
+                ***************\n\n",
+                   self.number_of_reboots
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+// La traza en castellano de siempre.
+pub struct TextTrace;
+
+impl<'a> Observer<PathFinder<'a>> for TextTrace {
+    fn on_start(&mut self, solver: &PathFinder<'a>) {
+        println!("{}", solver);
+    }
+
+    fn on_iteration(&mut self, solver: &PathFinder<'a>) {
+        print!("{}", solver);
+    }
+}
+
+impl<'a> PathFinder<'a> {
+    // Vecino mas cercano desde el origen. Las ciudades se recorren en orden, que
+    // es el orden en el que aparecen sus aristas en la matriz, para desempatar
+    // siempre igual.
+    fn generate_greedy_solution(&mut self) -> Vec<usize> {
+        let mut first_solution: Vec<usize> = Vec::with_capacity(self.solution_size);
+        let mut in_solution = vec![false; self.solution_size + 1];
+        let mut node_from: usize = 0;
+        for _ in 0..self.solution_size {
+            let depot_allowed = first_solution.is_empty() || first_solution.len() == (self.solution_size - 2);
+            let node_to = (0..self.solution_size + 1)
+                .filter(|&node| node != node_from)
+                .filter(|&node| !in_solution[node_from] || !in_solution[node])
+                .filter(|&node| (node_from != 0 && node != 0) || depot_allowed)
+                .min_by_key(|&node| tour::distance(&self.cost_map, node_from, node))
+                .expect("Fail on greedy solution");
+
+            first_solution.push(node_to);
+            in_solution[node_to] = true;
+            node_from = node_to;
+        }
+        first_solution
+    }
+
+    pub fn new(cost_map: TriangularMatrix<usize>, rand_gen: &'a mut dyn RandomGenerator,
+               initial_solution: Option<Vec<usize>>, parameters: Parameters) -> PathFinder<'a> {
+        let solution_size = cost_map.number_of_lines - 1;
+        let taboo_list = LinkedHashSet::new();
+        let candidate_lists = match parameters.candidates {
+            0 => None,
+            size => Some(CandidateLists::new(&cost_map, size)),
+        };
+
+        let mut next_path_finder = PathFinder {
+            rand_gen,
+            cost_map,
+            current_solution: Vec::new(),
+            best_solution: Vec::new(),
+            best_solution_iteration: 0,
+            solution_size,
+            best_cost: 0.0,
+            taboo_list,
+            non_improvement_iterations: 0,
+            total_iterations: 0,
+            evaluations: 1,
+            number_of_reboots: 1,
+            rebooted: false,
+            last_move: None,
+            without_moves: false,
+            freq_mat: FreqMatrix::new(solution_size + 1),
+            candidate_lists,
+            parameters,
+            observers: Vec::new(),
+        };
+        next_path_finder.current_solution = match initial_solution {
+            Some(solution) => solution,
+            None => next_path_finder.generate_greedy_solution(),
+        };
+        next_path_finder.best_cost = next_path_finder.calculate_cost(&next_path_finder.current_solution);
+        next_path_finder.best_solution = next_path_finder.current_solution.clone();
+        next_path_finder
+    }
+
+    // Continua la busqueda desde un punto de control guardado con save_state.
+    pub fn resume(cost_map: TriangularMatrix<usize>, rand_gen: &'a mut dyn RandomGenerator,
+                  checkpoint: &Checkpoint, parameters: Parameters) -> Result<PathFinder<'a>, ConfigError> {
+        let solution_size = cost_map.number_of_lines - 1;
+        let current_solution = checkpoint.get_tour("current_solution", solution_size)?;
+        let mut path_finder = PathFinder::new(cost_map, rand_gen, Some(current_solution), parameters);
+        path_finder.best_solution = checkpoint.get_tour("best_solution", solution_size)?;
+        path_finder.best_cost = checkpoint.get("best_cost")?;
+        path_finder.best_solution_iteration = checkpoint.get("best_solution_iteration")?;
+        path_finder.taboo_list = checkpoint.get_pairs("taboo_list")?.into_iter().collect();
+        path_finder.non_improvement_iterations = checkpoint.get("non_improvement_iterations")?;
+        path_finder.total_iterations = checkpoint.get("total_iterations")?;
+        path_finder.evaluations = checkpoint.get("evaluations")?;
+        path_finder.number_of_reboots = checkpoint.get("number_of_reboots")?;
+        path_finder.rebooted = checkpoint.get("rebooted")?;
+        if path_finder.total_iterations > 0 {
+            path_finder.last_move = Some(checkpoint.get_move("last_move", solution_size)?);
+        }
+        path_finder.freq_mat.restore_counts(&checkpoint.get_list("freq_mat")?)
+            .map_err(|message| checkpoint.error("freq_mat", message))?;
+        checkpoint.restore_random(path_finder.rand_gen)?;
+        Ok(path_finder)
+    }
+
+    pub fn save_state(&self, checkpoint: &mut Checkpoint) {
+        checkpoint.set_list("current_solution", &self.current_solution);
+        checkpoint.set_list("best_solution", &self.best_solution);
+        checkpoint.set("best_cost", self.best_cost);
+        checkpoint.set("best_solution_iteration", self.best_solution_iteration);
+        checkpoint.set_pairs("taboo_list", self.taboo_list.iter().cloned());
+        checkpoint.set("non_improvement_iterations", self.non_improvement_iterations);
+        checkpoint.set("total_iterations", self.total_iterations);
+        checkpoint.set("evaluations", self.evaluations);
+        checkpoint.set("number_of_reboots", self.number_of_reboots);
+        checkpoint.set("rebooted", self.rebooted);
+        if let Some(movement) = self.last_move {
+            checkpoint.set("last_move", movement.code());
+        }
+        checkpoint.set_list("freq_mat", self.freq_mat.counts());
+        checkpoint.set_random(self.rand_gen);
+        checkpoint.set_parameters(&self.parameters);
+    }
+
+    pub fn add_observer(&mut self, observer: Box<dyn Observer<PathFinder<'a>> + 'a>) {
+        self.observers.push(observer);
+    }
+
+    // Los observadores se sacan mientras se les avisa para que puedan recibir &self.
+    fn notify(&mut self, event: impl Fn(&mut dyn Observer<PathFinder<'a>>, &PathFinder<'a>)) {
+        let mut observers = mem::take(&mut self.observers);
+        for observer in observers.iter_mut() {
+            event(observer.as_mut(), self);
+        }
+        self.observers = observers;
+    }
+
+    pub fn calculate_cost(&self, solution: &[usize]) -> f64 {
+        tour::calculate_cost(&self.cost_map, solution)
+    }
+
+
+    // Los aleatorios de todos los intentos se sacan antes de empezar. None, sin
+    // cambiar nada, si el generador se agota.
+    fn reboot_diversification(&mut self) -> Option<()> {
+        let mut best_vec = Vec::new();
+        let mut best_cost = f64::MAX;
+
+        let delta_cost = (*self.cost_map.get_max() - *self.cost_map.get_min()) as f64;
+        let swaps = self.current_solution.len() / self.parameters.divisor_change_on_reboot;
+        let randoms = random_generator::draw_all(self.rand_gen, |rand_gen| {
+            (0..self.parameters.tries_on_reboot * swaps * 2).map(|_| rand_gen.next_random()).collect::<Option<Vec<f64>>>()
+        })?;
+        let mut randoms = randoms.into_iter();
+
+        for _ in 0..self.parameters.tries_on_reboot {
+            let mut new_vec = self.current_solution.clone();
+            for _ in 0..swaps {
+                let rand_num1 = randoms.next().unwrap();
+                let rand_num2 = randoms.next().unwrap();
+                let rand_multiplier = (self.solution_size / 4) as f64;
+                let rand_position1 = (rand_num1 * rand_multiplier).floor() as usize + 1;
+                let rand_position2 = (rand_num2 * rand_multiplier).floor() as usize + 1;
+                new_vec.swap(rand_position1, rand_position2);
+            }
+
+            let freq_cost = self.freq_mat.get_solution_freq_cost(&new_vec);
+            let new_cost = self.calculate_cost(&new_vec) + freq_cost * delta_cost
+                * self.parameters.repetition_const;
+
+            if new_cost < best_cost {
+                best_cost = new_cost;
+                best_vec = new_vec;
+            }
+        }
+        self.current_solution = best_vec;
+        self.evaluations += self.parameters.tries_on_reboot;
+        self.non_improvement_iterations = 0;
+        self.taboo_list.clear();
+        self.number_of_reboots += 1;
+        Some(())
+    }
+
+    fn reboot_intensification(&mut self){
+        self.current_solution = self.best_solution.clone();
+        self.non_improvement_iterations = 0;
+        self.number_of_reboots += 1;
+    }
+
+    // None si no se ha podido reiniciar porque se han agotado los aleatorios.
+    fn reboot_if_necessary(&mut self) -> Option<()> {
+        if self.non_improvement_iterations <= self.parameters.reboot_on_it {
+            self.rebooted = false;
+            return Some(());
+        }
+
+        if self.number_of_reboots.is_multiple_of(self.parameters.intensification_mod) {
+            self.reboot_intensification();
+        } else {
+            self.reboot_diversification()?;
+        }
+        self.rebooted = true;
+        let restart = self.number_of_reboots - 1;
+        self.notify(|observer, solver| observer.on_restart(solver, restart));
+        Some(())
+    }
+
+    fn save_current_if_it_is_the_best(&mut self, current_solution_cost: f64) {
+        if current_solution_cost < self.best_cost {
+            self.best_cost = current_solution_cost;
+            self.best_solution = self.current_solution.clone();
+            self.non_improvement_iterations = 0;
+            self.best_solution_iteration = self.total_iterations;
+            self.notify(|observer, solver| observer.on_new_best(solver, current_solution_cost));
+        } else {
+            self.non_improvement_iterations += 1;
+        }
+    }
+
+    fn update_taboo_list(&mut self, swap: (usize, usize)) {
+        if self.taboo_list.len() == self.parameters.taboo_list_max_elements {
+            self.taboo_list.pop_front();
+        }
+        self.taboo_list.insert(swap);
+    }
+
+    // Aspiracion por defecto: si todos los movimientos son tabu se aplica el
+    // que lleva mas tiempo prohibido, y entre esos el mejor. Con el mejor sin
+    // mas se desharia una y otra vez el ultimo movimiento.
+    fn least_taboo_neighbour(&self, positions: &[usize], current_cost: f64) -> (Option<(Move, f64)>, usize) {
+        let ages: collections::HashMap<(usize, usize), usize> = self.taboo_list.iter().enumerate()
+            .map(|(age, &pair)| (pair, age))
+            .collect();
+        let age = |movement: &Move| cmp::max(ages.get(&movement.positions()), ages.get(&movement.undo_positions()));
+        let neighbourhood = self.parameters.neighbourhood;
+        let moves: Box<dyn Iterator<Item = Move>> = match self.candidate_lists {
+            Some(ref candidate_lists) => Box::new(candidate_lists.moves(neighbourhood, &self.current_solution, positions)),
+            None => Box::new(neighbourhood.moves(self.solution_size)),
+        };
+        let mut candidates = 0;
+        let best = moves
+            .inspect(|_| candidates += 1)
+            .map(|movement| (movement, current_cost + movement.delta(&self.cost_map, &self.current_solution)))
+            .min_by_key(|&(movement, cost)| (age(&movement), OrderedFloat(cost)));
+        (best, candidates)
+    }
+
+    fn next_solution(&mut self) {
+        if self.reboot_if_necessary().is_none() {
+            return;
+        }
+        let cost_map = &self.cost_map;
+        let current_solution = &self.current_solution;
+        let taboo_list = &self.taboo_list;
+        let neighbourhood = self.parameters.neighbourhood;
+        let solution_size = self.solution_size;
+        let current_cost = self.calculate_cost(current_solution);
+        let best_allowed = |moves: &mut dyn Iterator<Item = Move>| {
+            let mut candidates = 0;
+            let best = moves
+                .filter(|movement| !taboo_list.contains(&movement.positions())
+                    && !taboo_list.contains(&movement.undo_positions()))
+                .inspect(|_| candidates += 1)
+                .map(|movement| (movement, current_cost + movement.delta(cost_map, current_solution)))
+                .min_by_key(|&(_, cost)| OrderedFloat(cost));
+            (best, candidates)
+        };
+        let positions = match self.candidate_lists {
+            Some(_) => candidates::positions(current_solution),
+            None => Vec::new(),
+        };
+        // Cada fila, o cada ciudad con listas de candidatos, se evalua en un
+        // hilo; la reduccion conserva el orden, asi que entre movimientos del
+        // mismo coste gana siempre el primero.
+        let (best, candidates) = match self.candidate_lists {
+            Some(ref candidate_lists) => (0..solution_size + 1).into_par_iter()
+                .map(|node| best_allowed(&mut candidate_lists
+                    .moves_around(neighbourhood, current_solution, &positions, node).into_iter()))
+                .reduce(|| (None, 0), first_best),
+            None => (0..neighbourhood.rows(solution_size)).into_par_iter()
+                .map(|row| best_allowed(&mut neighbourhood.row(solution_size, row)))
+                .reduce(|| (None, 0), first_best),
+        };
+        let (best, candidates) = match best {
+            None => self.least_taboo_neighbour(&positions, current_cost),
+            allowed => (allowed, candidates),
+        };
+        let Some((best_move, best_neighbour_cost)) = best else {
+            self.without_moves = true;
+            return;
+        };
+        self.evaluations += candidates;
+
+        self.notify(|observer, solver| observer.on_move_evaluated(solver, Change::Move(best_move), best_neighbour_cost));
+        best_move.apply(&mut self.current_solution);
+
+        self.freq_mat.insert_solution(&self.current_solution);
+
+        self.total_iterations += 1;
+        self.update_taboo_list(best_move.positions());
+        self.last_move = Some(best_move);
+        self.save_current_if_it_is_the_best(best_neighbour_cost);
+        self.notify(|observer, solver| observer.on_move_accepted(solver, Change::Move(best_move), best_neighbour_cost));
+        self.notify(|observer, solver| observer.on_iteration(solver));
+    }
+
+    pub fn trace_record(&self) -> TraceRecord {
+        TraceRecord {
+            iteration: self.total_iterations,
+            movement: self.last_move.map(Change::Move),
+            current_cost: self.calculate_cost(&self.current_solution),
+            best_cost: self.best_cost,
+            temperature: None,
+            taboo_list_size: Some(self.taboo_list.len()),
+            accepted: self.total_iterations > 0,
+            restart: self.rebooted,
+        }
+    }
+}
+
+impl<'a> Solver for PathFinder<'a> {
+    fn initialise(&mut self) {
+        self.notify(|observer, solver| observer.on_start(solver));
+    }
+
+    fn step(&mut self) {
+        self.next_solution();
+    }
+
+    // Se detiene por el numero de iteraciones, salvo que no haya ningun
+    // movimiento posible o se agoten los aleatorios.
+    fn is_finished(&self) -> bool {
+        self.without_moves || self.rand_gen.is_exhausted()
+    }
+
+    fn finish(&mut self) {
+        self.notify(|observer, solver| observer.on_finish(solver));
+    }
+
+    fn iteration(&self) -> usize {
+        self.total_iterations
+    }
+
+    fn evaluations(&self) -> usize {
+        self.evaluations
+    }
+
+    fn current_tour(&self) -> &[usize] {
+        &self.current_solution
+    }
+
+    fn current_cost(&self) -> f64 {
+        self.calculate_cost(&self.current_solution)
+    }
+
+    fn best_tour(&self) -> &[usize] {
+        &self.best_solution
+    }
+
+    fn best_cost(&self) -> f64 {
+        self.best_cost
+    }
+
+    fn summary(&self) -> String {
+        format!("\
+            \nMEJOR SOLUCION: \n\
+            \tRECORRIDO: {}\n\
+            \tCOSTE (km): {}\n\
+            \tITERACION: {}\n\
+            ",
+                tour::format_solution(&self.best_solution),
+                self.calculate_cost(&self.best_solution),
+                self.best_solution_iteration
+        )
+    }
+}
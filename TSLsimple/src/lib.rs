@@ -0,0 +1,475 @@
+use std::*;
+use std::collections::HashMap;
+
+extern crate tsl_core;
+
+use tsl_core::candidates;
+use tsl_core::candidates::CandidateLists;
+use tsl_core::candidates::DontLookBits;
+use tsl_core::config;
+use tsl_core::config::Configurable;
+use tsl_core::neighbourhood::Move;
+use tsl_core::neighbourhood::Neighbourhood;
+use tsl_core::observer::Change;
+use tsl_core::observer::Observer;
+use tsl_core::triangular::TriangularMatrix;
+use tsl_core::random_generator::RandomGenerator;
+use tsl_core::solver::Solver;
+use tsl_core::solver::SolverError;
+use tsl_core::tour;
+use tsl_core::trace::TraceRecord;
+
+// Regla para elegir a que vecino mejor se pasa en cada iteracion.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Pivot {
+    // El primero que mejora, recorriendo el vecindario desde un par sorteado.
+    RandomStart,
+    // El que mas mejora de todo el vecindario. Con listas de candidatos, el que
+    // mas mejora de los de la ciudad que se esta mirando, no de todas.
+    Best,
+    // El primero que mejora, en el orden del vecindario.
+    First,
+    // El primero que mejora, en un orden barajado de nuevo en cada iteracion.
+    Shuffled,
+}
+
+impl Pivot {
+    pub const NAMES: &'static [&'static str] = &["random-start", "best", "first", "shuffled"];
+
+    pub fn from_name(name: &str) -> Option<Pivot> {
+        match name {
+            "random-start" => Some(Pivot::RandomStart),
+            "best" => Some(Pivot::Best),
+            "first" => Some(Pivot::First),
+            "shuffled" => Some(Pivot::Shuffled),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Pivot::RandomStart => "random-start",
+            Pivot::Best => "best",
+            Pivot::First => "first",
+            Pivot::Shuffled => "shuffled",
+        }
+    }
+}
+
+impl fmt::Display for Pivot {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl str::FromStr for Pivot {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Pivot, String> {
+        Pivot::from_name(name).ok_or_else(|| format!("regla de pivote desconocida: {}", name))
+    }
+}
+
+pub struct Parameters {
+    pub neighbourhood: Neighbourhood,
+    pub pivot: Pivot,
+    // Tamano de las listas de candidatos; con 0 se recorre el vecindario entero.
+    pub candidates: usize,
+}
+
+impl Default for Parameters {
+    fn default() -> Parameters {
+        Parameters {
+            neighbourhood: Neighbourhood::Swap,
+            pivot: Pivot::RandomStart,
+            candidates: 0,
+        }
+    }
+}
+
+impl Configurable for Parameters {
+    fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+        match key {
+            "neighbourhood" => self.neighbourhood = config::parse_value(key, value)?,
+            "pivot" => self.pivot = config::parse_value(key, value)?,
+            "candidates" => self.candidates = config::parse_value(key, value)?,
+            _ => return Err(config::unknown_key(key)),
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Parameters {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "\tneighbourhood = \"{}\"", self.neighbourhood)?;
+        writeln!(f, "\tpivot = \"{}\"", self.pivot)?;
+        writeln!(f, "\tcandidates = {}", self.candidates)
+    }
+}
+
+pub struct PathFinder<'a> {
+    rand_gen: &'a mut dyn RandomGenerator,
+    cost_map: TriangularMatrix<usize>,
+    current_solution: Vec<usize>,
+    solution_size: usize,
+    current_cost: f64,
+    total_iterations: usize,
+    evaluations: usize,
+    neighbourhood: Neighbourhood,
+    pivot: Pivot,
+    // Primer indice de cada fila del vecindario en la numeracion de todos sus
+    // movimientos; el ultimo es el numero de movimientos.
+    row_starts: Vec<usize>,
+    // Solo con Pivot::RandomStart y sin listas de candidatos.
+    visited_moves: Vec<bool>,
+    // Con listas de candidatos, la posicion de cada ciudad y las ciudades que
+    // quedan por mirar.
+    candidate_lists: Option<CandidateLists>,
+    positions: Vec<usize>,
+    dont_look_bits: DontLookBits,
+    last_movement: Option<Move>,
+    local_optimum: bool,
+    observers: Vec<Box<dyn Observer<PathFinder<'a>> + 'a>>,
+}
+
+// La traza en castellano de siempre: cada vecino evaluado y cada mejora.
+#[derive(Default)]
+pub struct TextTrace {
+    evaluated_neighbours: usize,
+}
+
+impl<'a> Observer<PathFinder<'a>> for TextTrace {
+    fn on_start(&mut self, solver: &PathFinder<'a>) {
+        println!("\nSOLUCION S_{} -> {:?}; {}km", solver.total_iterations, solver.current_solution, solver.current_cost);
+    }
+
+    fn on_move_evaluated(&mut self, solver: &PathFinder<'a>, movement: Change, cost: f64) {
+        let Change::Move(movement) = movement else {
+            unreachable!("la busqueda local solo aplica movimientos del vecindario");
+        };
+        let neighbour = movement.applied_to(&solver.current_solution);
+        println!("\tVECINO V_{} -> {}; {:?}; {}km", self.evaluated_neighbours, movement, neighbour, cost);
+        self.evaluated_neighbours += 1;
+    }
+
+    fn on_move_accepted(&mut self, solver: &PathFinder<'a>, _movement: Change, _cost: f64) {
+        self.evaluated_neighbours = 0;
+        println!("\nSOLUCION S_{} -> {:?}; {}km", solver.total_iterations, solver.current_solution, solver.current_cost);
+    }
+}
+
+impl<'a> PathFinder<'a> {
+    pub fn new(cost_map: TriangularMatrix<usize>, rand_gen: &'a mut dyn RandomGenerator,
+               initial_solution: Option<Vec<usize>>, parameters: Parameters) -> Result<PathFinder<'a>, SolverError> {
+        let solution_size = cost_map.number_of_lines - 1;
+        let first_solution = match initial_solution {
+            Some(solution) => solution,
+            None => tour::generate_rand_solution(rand_gen, solution_size).ok_or(SolverError::RandomExhausted)?,
+        };
+        let candidate_lists = match parameters.candidates {
+            0 => None,
+            size => Some(CandidateLists::new(&cost_map, size)),
+        };
+        let (positions, dont_look_bits) = match candidate_lists {
+            Some(_) => (candidates::positions(&first_solution),
+                        DontLookBits::new(first_look_order(rand_gen, &first_solution, parameters.pivot)
+                                              .ok_or(SolverError::RandomExhausted)?,
+                                          solution_size + 1)),
+            None => (Vec::new(), DontLookBits::new(iter::empty(), 0)),
+        };
+        let neighbourhood = parameters.neighbourhood;
+        let pivot = parameters.pivot;
+        let mut row_starts = vec![0];
+        for row in 0..neighbourhood.rows(solution_size) {
+            row_starts.push(row_starts[row] + neighbourhood.row_len(solution_size, row));
+        }
+
+        let mut next_path_finder = PathFinder {
+            rand_gen,
+            cost_map,
+            current_solution: first_solution,
+            solution_size,
+            current_cost: 0.0,
+            total_iterations: 0,
+            evaluations: 1,
+            visited_moves: match (pivot, &candidate_lists) {
+                (Pivot::RandomStart, None) => vec![false; *row_starts.last().unwrap()],
+                _ => Vec::new(),
+            },
+            positions,
+            candidate_lists,
+            dont_look_bits,
+            neighbourhood,
+            pivot,
+            row_starts,
+            last_movement: None,
+            local_optimum: false,
+            observers: Vec::new(),
+        };
+        next_path_finder.current_cost = next_path_finder.calculate_cost(&next_path_finder.current_solution);
+
+        Ok(next_path_finder)
+    }
+
+    pub fn add_observer(&mut self, observer: Box<dyn Observer<PathFinder<'a>> + 'a>) {
+        self.observers.push(observer);
+    }
+
+    // Los observadores se sacan mientras se les avisa para que puedan recibir &self.
+    fn notify(&mut self, event: impl Fn(&mut dyn Observer<PathFinder<'a>>, &PathFinder<'a>)) {
+        let mut observers = mem::take(&mut self.observers);
+        for observer in observers.iter_mut() {
+            event(observer.as_mut(), self);
+        }
+        self.observers = observers;
+    }
+
+    pub fn calculate_cost(&self, solution: &[usize]) -> f64 {
+        tour::calculate_cost(&self.cost_map, solution)
+    }
+
+    // Se sortea un par de posiciones i > j y, desde la columna j de la fila i
+    // del vecindario (a escala si la fila no tiene i movimientos), se recorren
+    // los movimientos en circulo hasta el primero sin evaluar. None tambien si
+    // el generador se agota.
+    fn next_neighbour(&mut self) -> Option<Move> {
+        let swap_i = (self.solution_size as f64 * self.rand_gen.next_random()?).floor() as usize;
+        let swap_j = (self.solution_size as f64 * self.rand_gen.next_random()?).floor() as usize;
+        let mut i = cmp::max(swap_i, swap_j);
+        let mut j = cmp::min(swap_i, swap_j);
+
+        if i == j {
+            i = cmp::max((i + 1) % self.solution_size, 1);
+            j = 0;
+        }
+        let start = self.row_starts[i] + j * self.neighbourhood.row_len(self.solution_size, i) / i;
+
+        let index = (start..self.visited_moves.len()).chain(0..start)
+            .find(|&index| !self.visited_moves[index])?;
+        self.visited_moves[index] = true;
+        Some(self.move_with_index(index))
+    }
+
+    fn move_with_index(&self, index: usize) -> Move {
+        let row = self.row_starts.partition_point(|&row_start| row_start <= index) - 1;
+        self.neighbourhood.move_at(self.solution_size, row, index - self.row_starts[row])
+    }
+
+    fn evaluate(&mut self, movement: Move) -> f64 {
+        let next_cost = self.current_cost + movement.delta(&self.cost_map, &self.current_solution);
+        self.evaluations += 1;
+        self.notify(|observer, solver| observer.on_move_evaluated(solver, Change::Move(movement), next_cost));
+        next_cost
+    }
+
+    fn random_start_improvement(&mut self) -> Option<(Move, f64)> {
+        while let Some(movement) = self.next_neighbour() {
+            let next_cost = self.evaluate(movement);
+            if next_cost < self.current_cost {
+                self.visited_moves.iter_mut().for_each(|visited| *visited = false);
+                return Some((movement, next_cost));
+            }
+        }
+        None
+    }
+
+    fn best_improvement(&mut self) -> Option<(Move, f64)> {
+        let mut best = None;
+        let mut best_cost = self.current_cost;
+        for movement in self.neighbourhood.moves(self.solution_size) {
+            let next_cost = self.evaluate(movement);
+            // Con empates se queda el primero.
+            if next_cost < best_cost {
+                best = Some(movement);
+                best_cost = next_cost;
+            }
+        }
+        best.map(|movement| (movement, best_cost))
+    }
+
+    fn first_improvement(&mut self) -> Option<(Move, f64)> {
+        for movement in self.neighbourhood.moves(self.solution_size) {
+            let next_cost = self.evaluate(movement);
+            if next_cost < self.current_cost {
+                return Some((movement, next_cost));
+            }
+        }
+        None
+    }
+
+    // Fisher-Yates sobre los indices de los movimientos, sorteando cada uno
+    // justo antes de evaluarlo: solo se guardan las posiciones ya cambiadas y
+    // no se gastan aleatorios en la parte del orden que no se llega a ver.
+    fn shuffled_first_improvement(&mut self) -> Option<(Move, f64)> {
+        let total = *self.row_starts.last().unwrap();
+        let mut moved: HashMap<usize, usize> = HashMap::new();
+        for drawn in 0..total {
+            let pick = drawn + ((total - drawn) as f64 * self.rand_gen.next_random()?).floor() as usize;
+            let index = moved.get(&pick).copied().unwrap_or(pick);
+            moved.insert(pick, moved.get(&drawn).copied().unwrap_or(drawn));
+
+            let movement = self.move_with_index(index);
+            let next_cost = self.evaluate(movement);
+            if next_cost < self.current_cost {
+                return Some((movement, next_cost));
+            }
+        }
+        None
+    }
+
+    // Se mira cada vez una ciudad y solo los movimientos que la acercan a sus
+    // candidatas: con Pivot::Best se aplica el mejor de ellos y con las demas
+    // reglas el primero que mejora. Pivot::Best es asi el mejor de cada ciudad:
+    // buscar el mejor de todas las ciudades por mirar haria cada iteracion
+    // lineal en el tamano de la instancia. Si ninguno mejora la ciudad no se
+    // vuelve a mirar hasta que cambie una de sus aristas.
+    fn candidate_improvement(&mut self) -> Option<(Move, f64)> {
+        while let Some(node) = self.dont_look_bits.pop() {
+            let moves = self.candidate_lists.as_ref().unwrap()
+                .moves_around(self.neighbourhood, &self.current_solution, &self.positions, node);
+            let mut best = None;
+            let mut best_cost = self.current_cost;
+            for movement in moves {
+                let next_cost = self.evaluate(movement);
+                if next_cost < best_cost {
+                    best = Some(movement);
+                    best_cost = next_cost;
+                    if self.pivot != Pivot::Best {
+                        break;
+                    }
+                }
+            }
+            if let Some(movement) = best {
+                return Some((movement, best_cost));
+            }
+        }
+        None
+    }
+
+    fn next_solution(&mut self) {
+        self.last_movement = None;
+        let improvement = match self.pivot {
+            _ if self.candidate_lists.is_some() => self.candidate_improvement(),
+            Pivot::RandomStart => self.random_start_improvement(),
+            Pivot::Best => self.best_improvement(),
+            Pivot::First => self.first_improvement(),
+            Pivot::Shuffled => self.shuffled_first_improvement(),
+        };
+        let Some((movement, next_cost)) = improvement else {
+            // Sin aleatorios la iteracion se queda sin terminar y no se sabe si
+            // la solucion es un optimo local.
+            if self.rand_gen.is_exhausted() {
+                return;
+            }
+            self.local_optimum = true;
+            self.notify(|observer, solver| observer.on_iteration(solver));
+            return;
+        };
+
+        self.current_cost = next_cost;
+        if self.candidate_lists.is_some() {
+            self.dont_look_bits.wake_endpoints(&movement, &self.current_solution);
+            movement.apply(&mut self.current_solution);
+            candidates::update_positions(&mut self.positions, &self.current_solution, &movement);
+        } else {
+            movement.apply(&mut self.current_solution);
+        }
+        self.total_iterations += 1;
+        self.last_movement = Some(movement);
+        self.notify(|observer, solver| observer.on_new_best(solver, next_cost));
+        self.notify(|observer, solver| observer.on_move_accepted(solver, Change::Move(movement), next_cost));
+        self.notify(|observer, solver| observer.on_iteration(solver));
+    }
+
+    pub fn trace_record(&self) -> TraceRecord {
+        TraceRecord {
+            iteration: self.total_iterations,
+            movement: self.last_movement.map(Change::Move),
+            current_cost: self.current_cost,
+            best_cost: self.current_cost,
+            temperature: None,
+            taboo_list_size: None,
+            accepted: self.last_movement.is_some(),
+            restart: false,
+        }
+    }
+}
+
+// Orden en que se miran las ciudades la primera vez con listas de candidatos:
+// el del recorrido, desde el origen o, con Pivot::RandomStart, desde una
+// ciudad sorteada, o barajado con Pivot::Shuffled. None si el generador se
+// agota.
+fn first_look_order(rand_gen: &mut dyn RandomGenerator, solution: &[usize], pivot: Pivot) -> Option<Vec<usize>> {
+    let mut order = tour::to_cycle(solution);
+    match pivot {
+        Pivot::RandomStart => {
+            let start = (order.len() as f64 * rand_gen.next_random()?).floor() as usize;
+            order.rotate_left(start);
+        }
+        Pivot::Shuffled => {
+            for i in (1..order.len()).rev() {
+                let j = ((i + 1) as f64 * rand_gen.next_random()?).floor() as usize;
+                order.swap(i, j);
+            }
+        }
+        Pivot::Best | Pivot::First => {}
+    }
+    Some(order)
+}
+
+// La busqueda local solo avanza cuando mejora: la solucion actual es la mejor.
+impl<'a> Solver for PathFinder<'a> {
+    fn initialise(&mut self) {
+        self.notify(|observer, solver| observer.on_start(solver));
+    }
+
+    fn step(&mut self) {
+        self.next_solution();
+    }
+
+    // Termina en el primer optimo local o al agotarse los aleatorios.
+    fn is_finished(&self) -> bool {
+        self.local_optimum || self.rand_gen.is_exhausted()
+    }
+
+    fn finish(&mut self) {
+        self.notify(|observer, solver| observer.on_finish(solver));
+    }
+
+    fn iteration(&self) -> usize {
+        self.total_iterations
+    }
+
+    fn evaluations(&self) -> usize {
+        self.evaluations
+    }
+
+    fn current_tour(&self) -> &[usize] {
+        &self.current_solution
+    }
+
+    fn current_cost(&self) -> f64 {
+        self.current_cost
+    }
+
+    fn best_tour(&self) -> &[usize] {
+        &self.current_solution
+    }
+
+    fn best_cost(&self) -> f64 {
+        self.current_cost
+    }
+
+    fn summary(&self) -> String {
+        format!("\
+            \nMEJOR SOLUCION: \n\
+            \tRECORRIDO: {}\n\
+            \tCOSTE (km): {}\n\
+            \tITERACION: {}\n\
+            ",
+                tour::format_solution(&self.current_solution),
+                self.current_cost,
+                self.total_iterations
+        )
+    }
+}
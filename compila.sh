@@ -12,4 +12,4 @@ fi
 
 source $HOME/.cargo/env
 cargo build --release
-cp target/release/practica02 ./a.out
+cp target/release/tsl ./tsl
@@ -0,0 +1,1064 @@
+use std::*;
+use std::io::Write;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+
+extern crate clap;
+extern crate ctrlc;
+extern crate tsl_core;
+extern crate tsl_simple;
+extern crate tsl_taboo;
+extern crate tsl_taboo_kopt;
+extern crate tsl_sa_kopt;
+extern crate tsl_lin_kernighan;
+extern crate tsl_iterated_local_search;
+
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use clap::builder::{PossibleValuesParser, TypedValueParser};
+
+use tsl_core::candidates::CandidateLists;
+use tsl_core::checkpoint::Checkpoint;
+use tsl_core::checkpoint::CheckpointWriter;
+use tsl_core::config;
+use tsl_core::config::Configurable;
+use tsl_core::config::ExperimentFile;
+use tsl_core::error::LoadError;
+use tsl_core::instance;
+use tsl_core::neighbourhood::Neighbourhood;
+use tsl_core::observer::Observer;
+use tsl_core::tour;
+use tsl_core::trace::RecordFormat;
+use tsl_core::trace::RecordTrace;
+use tsl_core::trace::TraceRecord;
+use tsl_core::triangular::TriangularMatrix;
+use tsl_core::tsplib;
+use tsl_core::error::WriteFailure;
+use tsl_core::random_generator::Exhaustion;
+use tsl_core::random_generator::RandomGenerator;
+use tsl_core::random_generator::RandReader;
+use tsl_core::random_generator::RecordingRand;
+use tsl_core::random_generator::SeededRand;
+use tsl_core::solver;
+use tsl_core::solver::Solver;
+use tsl_core::solver::SolverError;
+use tsl_core::stopping::Criterion;
+use tsl_core::stopping::StoppingCriteria;
+
+const CHECKPOINT_EVERY: usize = 1000;
+// Algoritmos que pueden guardar y reanudar su estado.
+const CHECKPOINTED: [&str; 3] = ["tabu", "tabu-2opt", "anneal"];
+
+#[derive(Parser)]
+#[command(name = "tsl", about = "Problema del viajante resuelto con varias metaheurísticas")]
+struct Cli {
+    #[command(subcommand)]
+    algorithm: Algorithm,
+}
+
+#[derive(Subcommand)]
+enum Algorithm {
+    /// Búsqueda local por intercambio, se detiene en el primer óptimo local
+    LocalSearch(LocalSearchArgs),
+    /// Búsqueda tabú por intercambio de posiciones
+    Tabu(TabuArgs),
+    /// Búsqueda tabú por inversión de segmentos con reinicios por diversificación
+    #[command(name = "tabu-2opt")]
+    TabuTwoOpt(TabuTwoOptArgs),
+    /// Enfriamiento simulado por inversión de segmentos
+    Anneal(AnnealArgs),
+    /// Mejora de Lin-Kernighan, se detiene en el primer óptimo local
+    LinKernighan(LinKernighanArgs),
+    /// Búsqueda local iterada con 2-opt y Or-opt o Lin-Kernighan y perturbaciones de doble puente
+    IteratedLocalSearch(IteratedLocalSearchArgs),
+    /// Coste de un recorrido leído de un fichero .tour
+    Cost(CostArgs),
+}
+
+#[derive(Args)]
+struct CommonArgs {
+    /// Fichero de distancias
+    distances: Option<String>,
+
+    /// Fichero de experimento con los parámetros; los flags tienen prioridad
+    #[arg(short, long)]
+    config: Option<String>,
+
+    /// Fichero de aleatorios; sin él se usa un generador con semilla
+    #[arg(short, long)]
+    random: Option<String>,
+
+    /// Semilla del generador; sin ella se elige una al azar y se muestra
+    #[arg(short, long)]
+    seed: Option<u64>,
+
+    /// Termina con error si se agota el fichero de aleatorios en lugar de volver al principio
+    #[arg(long)]
+    strict_random: bool,
+
+    /// Fichero en el que se guardan los aleatorios consumidos
+    #[arg(long)]
+    record_random: Option<String>,
+
+    /// Número de iteraciones (en la búsqueda local, número máximo de mejoras)
+    #[arg(short = 'n', long)]
+    iterations: Option<usize>,
+
+    /// Tiempo máximo de búsqueda en segundos
+    #[arg(long)]
+    max_seconds: Option<f64>,
+
+    /// Número máximo de cálculos del coste de un recorrido
+    #[arg(long)]
+    max_evaluations: Option<usize>,
+
+    /// Se detiene al encontrar una solución con este coste o menor
+    #[arg(long)]
+    target_cost: Option<f64>,
+
+    /// Iteraciones seguidas sin mejorar la mejor solución tras las que se detiene
+    #[arg(long)]
+    stagnation: Option<usize>,
+
+    /// Formato de la salida [por defecto: text]
+    #[arg(short, long, value_enum)]
+    format: Option<OutputFormat>,
+
+    /// Fichero .tour con el recorrido desde el que se empieza
+    #[arg(long)]
+    initial_tour: Option<String>,
+
+    /// Fichero .tour en el que se guarda la mejor solución
+    #[arg(long)]
+    tour_out: Option<String>,
+
+    /// Numera los nodos del fichero .tour desde 1, como TSPLIB
+    #[arg(long)]
+    one_based: bool,
+}
+
+#[derive(Args)]
+struct LocalSearchArgs {
+    #[command(flatten)]
+    common: CommonArgs,
+
+    /// Vecindario de la búsqueda [por defecto: swap]
+    #[arg(long, value_parser = neighbourhood_parser())]
+    neighbourhood: Option<Neighbourhood>,
+
+    /// Vecino que mejora al que se pasa en cada iteración; con listas de candidatos, best es el mejor
+    /// de la ciudad que se mira [por defecto: random-start]
+    #[arg(long, value_parser = pivot_parser())]
+    pivot: Option<tsl_simple::Pivot>,
+
+    /// Tamaño de las listas de candidatos, solo con two-opt y or-opt [por defecto: 0, sin listas]
+    #[arg(long)]
+    candidates: Option<usize>,
+}
+
+fn pivot_parser() -> impl TypedValueParser<Value = tsl_simple::Pivot> {
+    PossibleValuesParser::new(tsl_simple::Pivot::NAMES).map(|name| tsl_simple::Pivot::from_name(&name).unwrap())
+}
+
+#[derive(Args)]
+struct CostArgs {
+    /// Fichero de distancias
+    distances: String,
+
+    /// Fichero .tour con el recorrido, numerado desde 0 o desde 1
+    tour: String,
+}
+
+// Solo para los algoritmos que pueden tardar mucho: tabu y enfriamiento simulado.
+#[derive(Args)]
+struct CheckpointArgs {
+    /// Fichero en el que se guarda periódicamente el estado completo de la búsqueda
+    #[arg(long)]
+    checkpoint: Option<String>,
+
+    /// Iteraciones entre dos puntos de control [por defecto: 1000]
+    #[arg(long)]
+    checkpoint_every: Option<usize>,
+
+    /// Punto de control desde el que se reanuda la búsqueda
+    #[arg(long)]
+    resume: Option<String>,
+}
+
+#[derive(Args)]
+struct TabuArgs {
+    #[command(flatten)]
+    common: CommonArgs,
+
+    #[command(flatten)]
+    checkpoint: CheckpointArgs,
+
+    /// Tamaño máximo de la lista tabú
+    #[arg(long, value_parser = positive_parser())]
+    taboo_list_max_elements: Option<usize>,
+
+    /// Iteraciones sin mejora tras las que se reinicia desde la mejor solución
+    #[arg(long)]
+    reboot_on_it: Option<usize>,
+
+    /// Vecindario de la búsqueda [por defecto: swap]
+    #[arg(long, value_parser = neighbourhood_parser())]
+    neighbourhood: Option<Neighbourhood>,
+
+    /// Tamaño de las listas de candidatos, solo con two-opt y or-opt [por defecto: 0, sin listas]
+    #[arg(long)]
+    candidates: Option<usize>,
+}
+
+#[derive(Args)]
+struct TabuTwoOptArgs {
+    #[command(flatten)]
+    common: CommonArgs,
+
+    #[command(flatten)]
+    checkpoint: CheckpointArgs,
+
+    /// Tamaño máximo de la lista tabú
+    #[arg(long, value_parser = positive_parser())]
+    taboo_list_max_elements: Option<usize>,
+
+    /// Iteraciones sin mejora tras las que se reinicia
+    #[arg(long)]
+    reboot_on_it: Option<usize>,
+
+    /// Divisor del tamaño del recorrido que da el número de intercambios al diversificar
+    #[arg(long, value_parser = positive_parser())]
+    divisor_change_on_reboot: Option<usize>,
+
+    /// Recorridos aleatorios generados en cada reinicio por diversificación
+    #[arg(long, value_parser = positive_parser())]
+    tries_on_reboot: Option<usize>,
+
+    /// Peso de la matriz de frecuencias al elegir el recorrido de diversificación
+    #[arg(long)]
+    repetition_const: Option<f64>,
+
+    /// Cada cuántos reinicios se intensifica en lugar de diversificar
+    #[arg(long)]
+    intensification_mod: Option<usize>,
+
+    /// Vecindario de la búsqueda [por defecto: two-opt]
+    #[arg(long, value_parser = neighbourhood_parser())]
+    neighbourhood: Option<Neighbourhood>,
+
+    /// Tamaño de las listas de candidatos, solo con two-opt y or-opt [por defecto: 0, sin listas]
+    #[arg(long)]
+    candidates: Option<usize>,
+}
+
+#[derive(Args)]
+struct AnnealArgs {
+    #[command(flatten)]
+    common: CommonArgs,
+
+    #[command(flatten)]
+    checkpoint: CheckpointArgs,
+
+    /// Probabilidad de aceptar al inicio un empeoramiento de mu veces el coste
+    #[arg(long)]
+    phi: Option<f64>,
+
+    /// Fracción del coste inicial usada para fijar la temperatura inicial
+    #[arg(long)]
+    mu: Option<f64>,
+
+    /// Candidatas probadas tras las que se enfría
+    #[arg(long)]
+    max_tested_candidates: Option<usize>,
+
+    /// Candidatas aceptadas tras las que se enfría
+    #[arg(long)]
+    max_accepted_candidates: Option<usize>,
+
+    /// Temperatura por debajo de la cual se detiene
+    #[arg(long)]
+    min_temperature: Option<f64>,
+
+    /// Vecindario de la búsqueda [por defecto: two-opt]
+    #[arg(long, value_parser = neighbourhood_parser())]
+    neighbourhood: Option<Neighbourhood>,
+}
+
+fn neighbourhood_parser() -> impl TypedValueParser<Value = Neighbourhood> {
+    PossibleValuesParser::new(Neighbourhood::NAMES).map(|name| Neighbourhood::from_name(&name).unwrap())
+}
+
+// Tamanos, divisores y numeros de intentos, que no pueden valer 0.
+fn positive_parser() -> impl TypedValueParser<Value = usize> {
+    clap::value_parser!(u64).range(1..).map(|value| value as usize)
+}
+
+#[derive(Args)]
+struct LinKernighanArgs {
+    #[command(flatten)]
+    common: CommonArgs,
+
+    /// Ciudades más cercanas a cada una que se prueban al añadir una arista
+    #[arg(long)]
+    neighbours: Option<usize>,
+
+    /// Número máximo de pasos de una cadena de cambios
+    #[arg(long, value_parser = positive_parser())]
+    max_depth: Option<usize>,
+
+    /// Alternativas que se prueban en el primer paso de cada cadena
+    #[arg(long)]
+    breadth: Option<usize>,
+}
+
+#[derive(Args)]
+struct IteratedLocalSearchArgs {
+    #[command(flatten)]
+    common: CommonArgs,
+
+    /// Tamaño de las listas de candidatos de la búsqueda local [por defecto: 8]
+    #[arg(long)]
+    candidates: Option<usize>,
+
+    /// Búsqueda local tras cada perturbación [por defecto: two-opt-or-opt]
+    #[arg(long, value_parser = PossibleValuesParser::new(tsl_iterated_local_search::LocalSearch::NAMES)
+          .map(|name| tsl_iterated_local_search::LocalSearch::from_name(&name).unwrap()))]
+    local_search: Option<tsl_iterated_local_search::LocalSearch>,
+
+    /// Perturbación tras cada óptimo local [por defecto: double-bridge]
+    #[arg(long, value_parser = PossibleValuesParser::new(tsl_iterated_local_search::Kick::NAMES)
+          .map(|name| tsl_iterated_local_search::Kick::from_name(&name).unwrap()))]
+    kick: Option<tsl_iterated_local_search::Kick>,
+
+    /// Ciudades del tramo que se baraja con segment-shuffle [por defecto: 10]
+    #[arg(long)]
+    segment_length: Option<usize>,
+
+    /// Criterio para quedarse con el nuevo óptimo local [por defecto: better]
+    #[arg(long, value_parser = PossibleValuesParser::new(tsl_iterated_local_search::Acceptance::NAMES)
+          .map(|name| tsl_iterated_local_search::Acceptance::from_name(&name).unwrap()))]
+    acceptance: Option<tsl_iterated_local_search::Acceptance>,
+
+    /// Perturbaciones sin mejora tras las que se reinicia con restart [por defecto: 100]
+    #[arg(long)]
+    restart_after: Option<usize>,
+}
+
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+enum OutputFormat {
+    /// Traza completa de cada iteración
+    Text,
+    /// Solo la mejor solución encontrada
+    Summary,
+    /// Un objeto JSON por iteración
+    Jsonl,
+    /// Una fila CSV por iteración
+    Csv,
+}
+
+impl OutputFormat {
+    fn record_format(self) -> Option<RecordFormat> {
+        match self {
+            OutputFormat::Jsonl => Some(RecordFormat::Jsonl),
+            OutputFormat::Csv => Some(RecordFormat::Csv),
+            OutputFormat::Text | OutputFormat::Summary => None,
+        }
+    }
+
+    fn is_structured(self) -> bool {
+        self.record_format().is_some()
+    }
+}
+
+struct Settings {
+    distances: String,
+    random: Option<String>,
+    seed: Option<u64>,
+    strict_random: bool,
+    record_random: Option<String>,
+    iterations: Option<usize>,
+    max_seconds: Option<f64>,
+    max_evaluations: Option<usize>,
+    target_cost: Option<f64>,
+    stagnation: Option<usize>,
+    min_temperature: Option<f64>,
+    checkpoint: Option<String>,
+    checkpoint_every: usize,
+    resume: Option<String>,
+    // Ultima iteracion que mejoro la mejor solucion, del punto de control que
+    // se reanuda.
+    last_improvement: Option<usize>,
+    format: OutputFormat,
+    initial_tour: Option<String>,
+    tour_out: Option<String>,
+    one_based: bool,
+    // Compartidos con el generador de aleatorios: el aviso de que se ha agotado
+    // el fichero estricto y la grabacion, que hay que volcar al salir de golpe.
+    random_exhaustion: Exhaustion,
+    // Con la grabacion y los puntos de control: el primer error al escribirlos.
+    write_failure: WriteFailure,
+    random_recording: cell::OnceCell<Arc<Mutex<io::BufWriter<fs::File>>>>,
+}
+
+// Se imprime con la sintaxis del fichero de experimento para poder repetir la ejecucion.
+struct Configuration<'a> {
+    settings: &'a Settings,
+    algorithm: &'a str,
+    parameters: &'a dyn fmt::Display,
+}
+
+impl<'a> fmt::Display for Configuration<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "CONFIGURACION:")?;
+        writeln!(f, "\tdistances = \"{}\"", self.settings.distances)?;
+        if let Some(ref random) = self.settings.random {
+            writeln!(f, "\trandom = \"{}\"", random)?;
+        }
+        if let Some(seed) = self.settings.seed {
+            writeln!(f, "\tseed = {}", seed)?;
+        }
+        if self.settings.strict_random {
+            writeln!(f, "\tstrict_random = true")?;
+        }
+        if let Some(ref record_random) = self.settings.record_random {
+            writeln!(f, "\trecord_random = \"{}\"", record_random)?;
+        }
+        if let Some(iterations) = self.settings.iterations {
+            writeln!(f, "\titerations = {}", iterations)?;
+        }
+        if let Some(max_seconds) = self.settings.max_seconds {
+            writeln!(f, "\tmax_seconds = {}", max_seconds)?;
+        }
+        if let Some(max_evaluations) = self.settings.max_evaluations {
+            writeln!(f, "\tmax_evaluations = {}", max_evaluations)?;
+        }
+        if let Some(target_cost) = self.settings.target_cost {
+            writeln!(f, "\ttarget_cost = {}", target_cost)?;
+        }
+        if let Some(stagnation) = self.settings.stagnation {
+            writeln!(f, "\tstagnation = {}", stagnation)?;
+        }
+        if let Some(min_temperature) = self.settings.min_temperature {
+            writeln!(f, "\tmin_temperature = {}", min_temperature)?;
+        }
+        if let Some(ref checkpoint) = self.settings.checkpoint {
+            writeln!(f, "\tcheckpoint = \"{}\"", checkpoint)?;
+            writeln!(f, "\tcheckpoint_every = {}", self.settings.checkpoint_every)?;
+        }
+        if let Some(ref resume) = self.settings.resume {
+            writeln!(f, "\tresume = \"{}\"", resume)?;
+        }
+        writeln!(f, "\tformat = \"{}\"", self.settings.format.to_possible_value().unwrap().get_name())?;
+        if let Some(ref initial_tour) = self.settings.initial_tour {
+            writeln!(f, "\tinitial_tour = \"{}\"", initial_tour)?;
+        }
+        if let Some(ref tour_out) = self.settings.tour_out {
+            writeln!(f, "\ttour_out = \"{}\"", tour_out)?;
+        }
+        if self.settings.one_based {
+            writeln!(f, "\tone_based = true")?;
+        }
+        writeln!(f, "\t[{}]", self.algorithm)?;
+        writeln!(f, "{}", self.parameters)
+    }
+}
+
+impl CommonArgs {
+    // Valores por defecto, despues el fichero de experimento y por ultimo los flags.
+    fn settings(&self, algorithm: &str, parameters: &mut dyn Configurable)
+                -> Result<Settings, Box<dyn error::Error>> {
+        let mut distances = None;
+        let mut random = None;
+        let mut seed = None;
+        let mut strict_random = None;
+        let mut record_random = None;
+        let mut iterations = None;
+        let mut max_seconds = None;
+        let mut max_evaluations = None;
+        let mut target_cost = None;
+        let mut stagnation = None;
+        let mut min_temperature = None;
+        let mut checkpoint = None;
+        let mut checkpoint_every = None;
+        let mut resume = None;
+        let mut format = None;
+        let mut initial_tour = None;
+        let mut tour_out = None;
+        let mut one_based = None;
+
+        if let Some(ref file_name) = self.config {
+            let experiment = ExperimentFile::from_file(file_name)?;
+            for entry in experiment.entries_for(algorithm) {
+                let result = match entry.key.as_str() {
+                    "distances" => {
+                        distances = Some(entry.value.clone());
+                        Ok(())
+                    }
+                    "random" => {
+                        random = Some(entry.value.clone());
+                        Ok(())
+                    }
+                    "seed" => config::parse_value(&entry.key, &entry.value)
+                        .map(|value| seed = Some(value)),
+                    "strict_random" => config::parse_value(&entry.key, &entry.value)
+                        .map(|value| strict_random = Some(value)),
+                    "record_random" => {
+                        record_random = Some(entry.value.clone());
+                        Ok(())
+                    }
+                    "iterations" => config::parse_value(&entry.key, &entry.value)
+                        .map(|value| iterations = Some(value)),
+                    "max_seconds" => config::parse_value(&entry.key, &entry.value)
+                        .map(|value| max_seconds = Some(value)),
+                    "max_evaluations" => config::parse_value(&entry.key, &entry.value)
+                        .map(|value| max_evaluations = Some(value)),
+                    "target_cost" => config::parse_value(&entry.key, &entry.value)
+                        .map(|value| target_cost = Some(value)),
+                    "stagnation" => config::parse_value(&entry.key, &entry.value)
+                        .map(|value| stagnation = Some(value)),
+                    // Solo el enfriamiento simulado tiene temperatura.
+                    "min_temperature" if algorithm == "anneal" => config::parse_value(&entry.key, &entry.value)
+                        .map(|value| min_temperature = Some(value)),
+                    // Las mejoras hasta el primer optimo local no admiten puntos de control.
+                    "checkpoint" if CHECKPOINTED.contains(&algorithm) => {
+                        checkpoint = Some(entry.value.clone());
+                        Ok(())
+                    }
+                    "checkpoint_every" if CHECKPOINTED.contains(&algorithm) => config::parse_value(&entry.key, &entry.value)
+                        .map(|value| checkpoint_every = Some(value)),
+                    "resume" if CHECKPOINTED.contains(&algorithm) => {
+                        resume = Some(entry.value.clone());
+                        Ok(())
+                    }
+                    "format" => OutputFormat::from_str(&entry.value, true)
+                        .map(|value| format = Some(value)),
+                    "initial_tour" => {
+                        initial_tour = Some(entry.value.clone());
+                        Ok(())
+                    }
+                    "tour_out" => {
+                        tour_out = Some(entry.value.clone());
+                        Ok(())
+                    }
+                    "one_based" => config::parse_value(&entry.key, &entry.value)
+                        .map(|value| one_based = Some(value)),
+                    key if entry.section.is_some() => parameters.set(key, &entry.value),
+                    key => Err(config::unknown_key(key)),
+                };
+                result.map_err(|message| experiment.error(entry, message))?;
+            }
+        }
+
+        // Un flag desplaza a la opcion contraria del fichero de experimento.
+        let (random, seed) = match (self.random.clone(), self.seed) {
+            (Some(_), Some(_)) => return Err("random y seed no se pueden usar a la vez".into()),
+            (Some(random), None) => (Some(random), None),
+            (None, Some(seed)) => (None, Some(seed)),
+            (None, None) if random.is_some() && seed.is_some() =>
+                return Err("random y seed no se pueden usar a la vez".into()),
+            (None, None) => (random, seed),
+        };
+        let seed = match random {
+            Some(_) => None,
+            None => Some(seed.unwrap_or_else(SeededRand::random_seed)),
+        };
+
+        Ok(Settings {
+            distances: self.distances.clone().or(distances)
+                .ok_or("falta el fichero de distancias")?,
+            random,
+            seed,
+            strict_random: self.strict_random || strict_random.unwrap_or(false),
+            record_random: self.record_random.clone().or(record_random),
+            iterations: self.iterations.or(iterations),
+            max_seconds: self.max_seconds.or(max_seconds),
+            max_evaluations: self.max_evaluations.or(max_evaluations),
+            target_cost: self.target_cost.or(target_cost),
+            stagnation: self.stagnation.or(stagnation),
+            min_temperature,
+            checkpoint,
+            checkpoint_every: checkpoint_every.unwrap_or(CHECKPOINT_EVERY),
+            resume,
+            last_improvement: None,
+            format: self.format.or(format).unwrap_or(OutputFormat::Text),
+            initial_tour: self.initial_tour.clone().or(initial_tour),
+            tour_out: self.tour_out.clone().or(tour_out),
+            one_based: self.one_based || one_based.unwrap_or(false),
+            random_exhaustion: Exhaustion::new(),
+            write_failure: WriteFailure::new(),
+            random_recording: cell::OnceCell::new(),
+        })
+    }
+}
+
+impl CheckpointArgs {
+    fn apply(&self, settings: &mut Settings) {
+        if let Some(ref value) = self.checkpoint { settings.checkpoint = Some(value.clone()); }
+        if let Some(value) = self.checkpoint_every { settings.checkpoint_every = value; }
+        if let Some(ref value) = self.resume { settings.resume = Some(value.clone()); }
+    }
+}
+
+impl LocalSearchArgs {
+    fn apply(&self, parameters: &mut tsl_simple::Parameters) {
+        if let Some(value) = self.neighbourhood { parameters.neighbourhood = value; }
+        if let Some(value) = self.pivot { parameters.pivot = value; }
+        if let Some(value) = self.candidates { parameters.candidates = value; }
+    }
+}
+
+impl TabuArgs {
+    fn apply(&self, parameters: &mut tsl_taboo::Parameters) {
+        if let Some(value) = self.taboo_list_max_elements { parameters.taboo_list_max_elements = value; }
+        if let Some(value) = self.reboot_on_it { parameters.reboot_on_it = value; }
+        if let Some(value) = self.neighbourhood { parameters.neighbourhood = value; }
+        if let Some(value) = self.candidates { parameters.candidates = value; }
+    }
+}
+
+impl TabuTwoOptArgs {
+    fn apply(&self, parameters: &mut tsl_taboo_kopt::Parameters) {
+        if let Some(value) = self.taboo_list_max_elements { parameters.taboo_list_max_elements = value; }
+        if let Some(value) = self.reboot_on_it { parameters.reboot_on_it = value; }
+        if let Some(value) = self.divisor_change_on_reboot { parameters.divisor_change_on_reboot = value; }
+        if let Some(value) = self.tries_on_reboot { parameters.tries_on_reboot = value; }
+        if let Some(value) = self.repetition_const { parameters.repetition_const = value; }
+        if let Some(value) = self.intensification_mod { parameters.intensification_mod = value; }
+        if let Some(value) = self.neighbourhood { parameters.neighbourhood = value; }
+        if let Some(value) = self.candidates { parameters.candidates = value; }
+    }
+}
+
+impl AnnealArgs {
+    fn apply(&self, parameters: &mut tsl_sa_kopt::Parameters) {
+        if let Some(value) = self.phi { parameters.phi = value; }
+        if let Some(value) = self.mu { parameters.mu = value; }
+        if let Some(value) = self.max_tested_candidates { parameters.max_tested_candidates = value; }
+        if let Some(value) = self.max_accepted_candidates { parameters.max_accepted_candidates = value; }
+        if let Some(value) = self.neighbourhood { parameters.neighbourhood = value; }
+    }
+}
+
+impl LinKernighanArgs {
+    fn apply(&self, parameters: &mut tsl_lin_kernighan::Parameters) {
+        if let Some(value) = self.neighbours { parameters.neighbours = value; }
+        if let Some(value) = self.max_depth { parameters.max_depth = value; }
+        if let Some(value) = self.breadth { parameters.breadth = value; }
+    }
+}
+
+impl IteratedLocalSearchArgs {
+    fn apply(&self, parameters: &mut tsl_iterated_local_search::Parameters) {
+        if let Some(value) = self.candidates { parameters.candidates = value; }
+        if let Some(value) = self.local_search { parameters.local_search = value; }
+        if let Some(value) = self.kick { parameters.kick = value; }
+        if let Some(value) = self.segment_length { parameters.segment_length = value; }
+        if let Some(value) = self.acceptance { parameters.acceptance = value; }
+        if let Some(value) = self.restart_after { parameters.restart_after = value; }
+    }
+}
+
+impl Settings {
+    fn random_generator(&self) -> Result<Box<dyn RandomGenerator>, Box<dyn error::Error>> {
+        let random_gen: Box<dyn RandomGenerator> = match self.random {
+            Some(ref file_name) if self.strict_random =>
+                Box::new(RandReader::new_strict(file_name, self.random_exhaustion.clone())?),
+            Some(ref file_name) => Box::new(RandReader::new(file_name)?),
+            None => Box::new(SeededRand::new(self.seed.unwrap_or_else(SeededRand::random_seed))),
+        };
+        // Al reanudar, los aleatorios se anaden a los que ya se habian grabado.
+        Ok(match self.record_random {
+            Some(ref file_name) => {
+                let recording = RecordingRand::new(random_gen, file_name, self.resume.is_some(),
+                                                   self.write_failure.clone())
+                    .map_err(|error| format!("{}: imposible escribir el fichero: {}", file_name, error))?;
+                let _ = self.random_recording.set(recording.writer());
+                Box::new(recording)
+            }
+            None => random_gen,
+        })
+    }
+
+    fn cost_map(&self) -> Result<TriangularMatrix<usize>, LoadError> {
+        instance::from_file(&self.distances)
+    }
+
+    fn initial_solution(&self, cost_map: &TriangularMatrix<usize>)
+                        -> Result<Option<Vec<usize>>, Box<dyn error::Error>> {
+        match self.initial_tour {
+            Some(ref file_name) => Ok(Some(load_tour(file_name, cost_map)?)),
+            None => Ok(None),
+        }
+    }
+
+    fn write_tour(&self, solution: &[usize], cost: f64) -> Result<(), Box<dyn error::Error>> {
+        if let Some(ref file_name) = self.tour_out {
+            let name = path::Path::new(&self.distances).file_stem()
+                .map_or(self.distances.clone(), |stem| stem.to_string_lossy().into_owned());
+            tsplib::write_tour(file_name, &name, &tour::to_cycle(solution), cost, self.one_based)
+                .map_err(|error| format!("{}: imposible escribir el fichero: {}", file_name, error))?;
+        }
+        Ok(())
+    }
+
+    // Sin ningun criterio de parada se hacen default_iterations iteraciones, si
+    // el algoritmo no termina por si mismo.
+    fn stopping_criteria(&self, default_iterations: Option<usize>)
+                         -> Result<StoppingCriteria, Box<dyn error::Error>> {
+        let mut criteria = StoppingCriteria::new()
+            .with_interruption(interruption_flag(self.random_recording.get().cloned())?)
+            .with_random_exhaustion(self.random_exhaustion.clone())
+            .with_write_failure(self.write_failure.clone());
+        if let Some(iteration) = self.last_improvement {
+            criteria = criteria.with_last_improvement(iteration);
+        }
+        if let Some(iterations) = self.iterations {
+            criteria = criteria.with(Criterion::Iterations(iterations));
+        }
+        if let Some(max_seconds) = self.max_seconds {
+            criteria = criteria.with(Criterion::Seconds(max_seconds));
+        }
+        if let Some(max_evaluations) = self.max_evaluations {
+            criteria = criteria.with(Criterion::Evaluations(max_evaluations));
+        }
+        if let Some(target_cost) = self.target_cost {
+            criteria = criteria.with(Criterion::TargetCost(target_cost));
+        }
+        if let Some(stagnation) = self.stagnation {
+            criteria = criteria.with(Criterion::Stagnation(stagnation));
+        }
+        if let Some(min_temperature) = self.min_temperature {
+            criteria = criteria.with(Criterion::Temperature(min_temperature));
+        }
+        Ok(match default_iterations {
+            Some(iterations) if criteria.is_empty() => criteria.with(Criterion::Iterations(iterations)),
+            _ => criteria,
+        })
+    }
+
+    // Si se ha interrumpido la busqueda, se han agotado los aleatorios o no se
+    // ha podido escribir un fichero el resumen se imprime siempre; lo segundo y
+    // lo tercero acaban ademas en error.
+    fn report_result(&self, solver: &dyn Solver, reached: Option<Criterion>, summary: bool)
+                     -> Result<(), Box<dyn error::Error>> {
+        let stopped_early = match reached {
+            Some(Criterion::Interrupted) => {
+                self.report(&"\nBUSQUEDA INTERRUMPIDA\n");
+                true
+            }
+            Some(Criterion::RandomExhausted(_)) => {
+                self.report(&"\nALEATORIOS AGOTADOS\n");
+                true
+            }
+            Some(Criterion::WriteFailed) => {
+                self.report(&"\nERROR DE ESCRITURA\n");
+                true
+            }
+            _ => false,
+        };
+        if summary || stopped_early {
+            self.report(&solver.summary());
+        }
+        self.write_tour(solver.best_tour(), solver.best_cost())?;
+        // La grabacion de los aleatorios se vuelca aqui para que un error al
+        // escribirla tambien acabe en error, como el del ultimo punto de control.
+        if let (Some(recording), Some(file_name)) = (self.random_recording.get(), self.record_random.as_ref()) {
+            let mut writer = recording.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+            if let Err(error) = writer.flush() {
+                self.write_failure.set(file_name, error);
+            }
+        }
+        if let Some(message) = self.write_failure.error() {
+            return Err(message.into());
+        }
+        match (reached, self.random.as_ref()) {
+            (Some(Criterion::RandomExhausted(consumed)), Some(file_name)) =>
+                Err(format!("{}: se han agotado los aleatorios tras consumir {}", file_name, consumed).into()),
+            _ => Ok(()),
+        }
+    }
+
+    // Los algoritmos no saben de que fichero salen los aleatorios.
+    fn solver_error(&self, error: SolverError) -> Box<dyn error::Error> {
+        match (error, self.random.as_ref()) {
+            (SolverError::RandomExhausted, Some(file_name)) =>
+                format!("{}: {}", file_name, SolverError::RandomExhausted).into(),
+            (error @ SolverError::TooFewCities { .. }, _) => format!("{}: {}", self.distances, error).into(),
+            (error, _) => error.into(),
+        }
+    }
+
+    // Los parametros guardados en el punto de control sustituyen a los del fichero
+    // de experimento; los flags se aplican despues.
+    fn checkpoint_to_resume(&mut self, algorithm: &str, parameters: &mut dyn Configurable)
+                            -> Result<Option<Checkpoint>, Box<dyn error::Error>> {
+        match self.resume {
+            Some(_) if self.initial_tour.is_some() =>
+                Err("initial_tour y resume no se pueden usar a la vez".into()),
+            Some(ref file_name) => {
+                let checkpoint = Checkpoint::from_file(file_name, algorithm)?;
+                checkpoint.apply_parameters(parameters)?;
+                self.last_improvement = Some(checkpoint.get("best_cost_iteration")?);
+                Ok(Some(checkpoint))
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn checkpoint_writer<'a, S: Solver + 'a>(&self, algorithm: &str, save_state: fn(&S, &mut Checkpoint))
+                                             -> Option<Box<dyn Observer<S> + 'a>> {
+        self.checkpoint.as_ref().map(|file_name| -> Box<dyn Observer<S> + 'a> {
+            let writer = CheckpointWriter::new(save_state, algorithm, file_name, self.checkpoint_every,
+                                               self.write_failure.clone());
+            Box::new(match self.last_improvement {
+                Some(iteration) => writer.with_last_improvement(iteration),
+                None => writer,
+            })
+        })
+    }
+
+    fn print_configuration(&self, algorithm: &str, parameters: &dyn fmt::Display) {
+        self.report(&Configuration { settings: self, algorithm, parameters });
+    }
+
+    // En los formatos para procesar la traza, la salida estandar solo lleva la traza.
+    fn report(&self, text: &dyn fmt::Display) {
+        if self.format.is_structured() {
+            eprint!("{}", text);
+        } else {
+            print!("{}", text);
+        }
+    }
+}
+
+// Solucion de un fichero .tour con todos los nodos de la instancia.
+fn load_tour(file_name: &str, cost_map: &TriangularMatrix<usize>) -> Result<Vec<usize>, Box<dyn error::Error>> {
+    let cycle = tsplib::read_tour(file_name)?;
+    if cycle.len() != cost_map.number_of_lines {
+        return Err(format!("{}: el recorrido tiene {} nodos y la instancia {}",
+                           file_name, cycle.len(), cost_map.number_of_lines).into());
+    }
+    Ok(tour::from_cycle(&cycle))
+}
+
+// Con SIGINT o SIGTERM se termina la iteracion en curso y se informa de la mejor
+// solucion; una segunda senal sale sin esperar, despues de volcar los aleatorios
+// grabados.
+fn interruption_flag(recording: Option<Arc<Mutex<io::BufWriter<fs::File>>>>)
+                     -> Result<Arc<AtomicBool>, Box<dyn error::Error>> {
+    let flag = Arc::new(AtomicBool::new(false));
+    let handler_flag = flag.clone();
+    ctrlc::set_handler(move || {
+        if handler_flag.swap(true, Ordering::SeqCst) {
+            if let Some(ref recording) = recording {
+                let mut writer = recording.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+                let _ = writer.flush();
+            }
+            process::exit(130);
+        }
+    })?;
+    Ok(flag)
+}
+
+// Observador que imprime la traza en el formato pedido, si lo hay.
+fn trace_observer<'a, S: 'a>(format: OutputFormat, text_trace: impl Observer<S> + 'a,
+                             trace_record: fn(&S) -> TraceRecord) -> Option<Box<dyn Observer<S> + 'a>> {
+    match format.record_format() {
+        Some(record_format) => Some(Box::new(RecordTrace::new(trace_record, record_format))),
+        None if format == OutputFormat::Text => Some(Box::new(text_trace)),
+        None => None,
+    }
+}
+
+fn check_candidates(candidates: usize, neighbourhood: Neighbourhood) -> Result<(), Box<dyn error::Error>> {
+    if candidates > 0 && !CandidateLists::supports(neighbourhood) {
+        return Err(format!("las listas de candidatos solo sirven con two-opt y or-opt, no con {}",
+                           neighbourhood).into());
+    }
+    Ok(())
+}
+
+fn local_search(args: &LocalSearchArgs) -> Result<(), Box<dyn error::Error>> {
+    let mut parameters = tsl_simple::Parameters::default();
+    let settings = args.common.settings("local-search", &mut parameters)?;
+    args.apply(&mut parameters);
+    check_candidates(parameters.candidates, parameters.neighbourhood)?;
+    settings.print_configuration("local-search", &parameters);
+
+    let cost_map = settings.cost_map()?;
+    let initial_solution = settings.initial_solution(&cost_map)?;
+    let mut random_gen = settings.random_generator()?;
+    let mut solver = tsl_simple::PathFinder::new(cost_map, &mut *random_gen, initial_solution, parameters)
+        .map_err(|error| settings.solver_error(error))?;
+    if let Some(observer) = trace_observer(settings.format, tsl_simple::TextTrace::default(),
+                                           tsl_simple::PathFinder::trace_record) {
+        solver.add_observer(observer);
+    }
+
+    let reached = solver::run(&mut solver, &mut settings.stopping_criteria(None)?);
+    settings.report_result(&solver, reached, settings.format != OutputFormat::Text)
+}
+
+fn tabu(args: &TabuArgs) -> Result<(), Box<dyn error::Error>> {
+    let mut parameters = tsl_taboo::Parameters::default();
+    let mut settings = args.common.settings("tabu", &mut parameters)?;
+    args.checkpoint.apply(&mut settings);
+    let checkpoint = settings.checkpoint_to_resume("tabu", &mut parameters)?;
+    args.apply(&mut parameters);
+    check_candidates(parameters.candidates, parameters.neighbourhood)?;
+    settings.print_configuration("tabu", &parameters);
+
+    let cost_map = settings.cost_map()?;
+    let initial_solution = settings.initial_solution(&cost_map)?;
+    let mut random_gen = settings.random_generator()?;
+    let mut solver = match checkpoint {
+        Some(ref checkpoint) => tsl_taboo::PathFinder::resume(cost_map, &mut *random_gen, checkpoint, parameters)?,
+        None => tsl_taboo::PathFinder::new(cost_map, &mut *random_gen, initial_solution, parameters)
+            .map_err(|error| settings.solver_error(error))?,
+    };
+    let mut criteria = settings.stopping_criteria(Some(tsl_taboo::NUMBER_OF_ITERATIONS))?;
+
+    if let Some(observer) = trace_observer(settings.format, tsl_taboo::TextTrace, tsl_taboo::PathFinder::trace_record) {
+        solver.add_observer(observer);
+    }
+    if let Some(observer) = settings.checkpoint_writer("tabu", tsl_taboo::PathFinder::save_state) {
+        solver.add_observer(observer);
+    }
+
+    let reached = solver::run(&mut solver, &mut criteria);
+    settings.report_result(&solver, reached, true)
+}
+
+fn tabu_two_opt(args: &TabuTwoOptArgs) -> Result<(), Box<dyn error::Error>> {
+    let mut parameters = tsl_taboo_kopt::Parameters::default();
+    let mut settings = args.common.settings("tabu-2opt", &mut parameters)?;
+    args.checkpoint.apply(&mut settings);
+    let checkpoint = settings.checkpoint_to_resume("tabu-2opt", &mut parameters)?;
+    args.apply(&mut parameters);
+    check_candidates(parameters.candidates, parameters.neighbourhood)?;
+    settings.print_configuration("tabu-2opt", &parameters);
+
+    let cost_map = settings.cost_map()?;
+    let initial_solution = settings.initial_solution(&cost_map)?;
+    let mut random_gen = settings.random_generator()?;
+    let mut solver = match checkpoint {
+        Some(ref checkpoint) => tsl_taboo_kopt::PathFinder::resume(cost_map, &mut *random_gen, checkpoint,
+                                                                   parameters)?,
+        None => tsl_taboo_kopt::PathFinder::new(cost_map, &mut *random_gen, initial_solution, parameters),
+    };
+    let mut criteria = settings.stopping_criteria(Some(tsl_taboo_kopt::NUMBER_OF_ITERATIONS))?;
+
+    if let Some(observer) = trace_observer(settings.format, tsl_taboo_kopt::TextTrace, tsl_taboo_kopt::PathFinder::trace_record) {
+        solver.add_observer(observer);
+    }
+    if let Some(observer) = settings.checkpoint_writer("tabu-2opt", tsl_taboo_kopt::PathFinder::save_state) {
+        solver.add_observer(observer);
+    }
+
+    let reached = solver::run(&mut solver, &mut criteria);
+    settings.report_result(&solver, reached, true)
+}
+
+fn anneal(args: &AnnealArgs) -> Result<(), Box<dyn error::Error>> {
+    let mut parameters = tsl_sa_kopt::Parameters::default();
+    let mut settings = args.common.settings("anneal", &mut parameters)?;
+    settings.min_temperature = args.min_temperature.or(settings.min_temperature);
+    args.checkpoint.apply(&mut settings);
+    let checkpoint = settings.checkpoint_to_resume("anneal", &mut parameters)?;
+    args.apply(&mut parameters);
+    settings.print_configuration("anneal", &parameters);
+
+    let cost_map = settings.cost_map()?;
+    let initial_solution = settings.initial_solution(&cost_map)?;
+    let mut random_gen = settings.random_generator()?;
+    let mut solver = match checkpoint {
+        Some(ref checkpoint) => tsl_sa_kopt::PathFinder::resume(cost_map, &mut *random_gen, checkpoint, parameters)?,
+        None => tsl_sa_kopt::PathFinder::new(cost_map, &mut *random_gen, initial_solution, parameters)
+            .map_err(|error| settings.solver_error(error))?,
+    };
+    let mut criteria = settings.stopping_criteria(Some(tsl_sa_kopt::NUMBER_OF_ITERATIONS))?;
+
+    if let Some(observer) = trace_observer(settings.format, tsl_sa_kopt::TextTrace, tsl_sa_kopt::PathFinder::trace_record) {
+        solver.add_observer(observer);
+    }
+    if let Some(observer) = settings.checkpoint_writer("anneal", tsl_sa_kopt::PathFinder::save_state) {
+        solver.add_observer(observer);
+    }
+
+    let reached = solver::run(&mut solver, &mut criteria);
+    settings.report_result(&solver, reached, true)
+}
+
+fn lin_kernighan(args: &LinKernighanArgs) -> Result<(), Box<dyn error::Error>> {
+    let mut parameters = tsl_lin_kernighan::Parameters::default();
+    let settings = args.common.settings("lin-kernighan", &mut parameters)?;
+    args.apply(&mut parameters);
+    settings.print_configuration("lin-kernighan", &parameters);
+
+    let cost_map = settings.cost_map()?;
+    let initial_solution = settings.initial_solution(&cost_map)?;
+    let mut random_gen = settings.random_generator()?;
+    let mut solver = tsl_lin_kernighan::PathFinder::new(cost_map, &mut *random_gen, initial_solution, parameters)
+        .map_err(|error| settings.solver_error(error))?;
+    if let Some(observer) = trace_observer(settings.format, tsl_lin_kernighan::TextTrace,
+                                           tsl_lin_kernighan::PathFinder::trace_record) {
+        solver.add_observer(observer);
+    }
+
+    let reached = solver::run(&mut solver, &mut settings.stopping_criteria(None)?);
+    settings.report_result(&solver, reached, settings.format != OutputFormat::Text)
+}
+
+fn iterated_local_search(args: &IteratedLocalSearchArgs) -> Result<(), Box<dyn error::Error>> {
+    let mut parameters = tsl_iterated_local_search::Parameters::default();
+    let settings = args.common.settings("iterated-local-search", &mut parameters)?;
+    args.apply(&mut parameters);
+    if parameters.candidates == 0 {
+        return Err("la búsqueda local iterada necesita listas de candidatos".into());
+    }
+    settings.print_configuration("iterated-local-search", &parameters);
+
+    let cost_map = settings.cost_map()?;
+    let initial_solution = settings.initial_solution(&cost_map)?;
+    let mut random_gen = settings.random_generator()?;
+    let mut solver = tsl_iterated_local_search::PathFinder::new(cost_map, &mut *random_gen, initial_solution,
+                                                                parameters)
+        .map_err(|error| settings.solver_error(error))?;
+    let mut criteria = settings.stopping_criteria(Some(tsl_iterated_local_search::NUMBER_OF_ITERATIONS))?;
+    if let Some(observer) = trace_observer(settings.format, tsl_iterated_local_search::TextTrace,
+                                           tsl_iterated_local_search::PathFinder::trace_record) {
+        solver.add_observer(observer);
+    }
+
+    let reached = solver::run(&mut solver, &mut criteria);
+    settings.report_result(&solver, reached, true)
+}
+
+fn cost(args: &CostArgs) -> Result<(), Box<dyn error::Error>> {
+    let cost_map = instance::from_file(&args.distances)?;
+    let solution = load_tour(&args.tour, &cost_map)?;
+
+    println!("RECORRIDO: {}", tour::format_solution(&solution));
+    println!("COSTE (km): {}", tour::calculate_cost(&cost_map, &solution));
+    Ok(())
+}
+
+fn main() {
+    let cli = Cli::parse();
+
+    let result = match cli.algorithm {
+        Algorithm::LocalSearch(ref args) => local_search(args),
+        Algorithm::Tabu(ref args) => tabu(args),
+        Algorithm::TabuTwoOpt(ref args) => tabu_two_opt(args),
+        Algorithm::Anneal(ref args) => anneal(args),
+        Algorithm::LinKernighan(ref args) => lin_kernighan(args),
+        Algorithm::IteratedLocalSearch(ref args) => iterated_local_search(args),
+        Algorithm::Cost(ref args) => cost(args),
+    };
+
+    if let Err(error) = result {
+        eprintln!("ERROR: {}", error);
+        process::exit(1);
+    }
+}
@@ -0,0 +1,340 @@
+use std::*;
+use std::path::Path;
+use std::path::PathBuf;
+use std::process;
+use std::process::Command;
+
+// Ejecuta cada algoritmo con los ficheros de distancias y aleatorios del
+// repositorio y compara la salida con las trazas de referencia. Cualquier cambio
+// en el orden de los vecinos o en el consumo de aleatorios cambia la traza.
+//
+// Con TSL_UPDATE_GOLDEN=1 se reescriben las trazas de tests/golden en lugar de
+// compararlas; la de la busqueda local es la original de TSLsimple y no se toca.
+
+fn workspace_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).parent().unwrap().to_path_buf()
+}
+
+fn golden_path(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("golden").join(name)
+}
+
+// Salida estandar y de errores de tsl, ejecutado desde la raiz del workspace
+// para que las rutas de la configuracion no dependan de la maquina.
+fn run_tsl(args: &[&str]) -> (String, String) {
+    let (success, stdout, stderr) = run_tsl_status(args);
+    assert!(success, "tsl {:?} ha fallado:\n{}", args, stderr);
+    (stdout, stderr)
+}
+
+// Como run_tsl, para las ejecuciones que pueden acabar en error.
+fn run_tsl_status(args: &[&str]) -> (bool, String, String) {
+    let output = Command::new(env!("CARGO_BIN_EXE_tsl"))
+        .args(args)
+        .current_dir(workspace_dir())
+        .output()
+        .expect("imposible ejecutar tsl");
+    (output.status.success(), String::from_utf8(output.stdout).unwrap(), String::from_utf8(output.stderr).unwrap())
+}
+
+fn assert_same_trace(name: &str, expected: &str, found: &str) {
+    let mut expected_lines = expected.lines();
+    let mut found_lines = found.lines();
+    for number in 1.. {
+        match (expected_lines.next(), found_lines.next()) {
+            (None, None) => return,
+            (expected_line, found_line) if expected_line == found_line => continue,
+            (expected_line, found_line) => panic!(
+                "{}: la traza cambia en la linea {}\n  esperada:   {:?}\n  encontrada: {:?}",
+                name, number, expected_line, found_line),
+        }
+    }
+}
+
+fn check_golden(name: &str, found: &str) {
+    let path = golden_path(name);
+    if env::var_os("TSL_UPDATE_GOLDEN").is_some() {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, found).unwrap();
+        return;
+    }
+    let expected = fs::read_to_string(&path)
+        .unwrap_or_else(|error| panic!("{}: {}", path.display(), error));
+    assert_same_trace(name, &expected, found);
+}
+
+// La traza CSV va a la salida estandar; la configuracion y la mejor solucion,
+// a la de errores.
+fn check_csv_run(name: &str, args: &[&str]) {
+    let mut args = args.to_vec();
+    args.extend_from_slice(&["--format", "csv"]);
+    let (trace, report) = run_tsl(&args);
+    check_golden(&format!("{}.csv", name), &trace);
+    check_golden(&format!("{}.txt", name), &report);
+}
+
+// Ejecuta args hasta la iteracion stop con un punto de control y lo continua
+// hasta iterations con --resume. La traza reanudada empieza con la fila de la
+// iteracion guardada y tiene que coincidir con la cola de la referencia de la
+// ejecucion sin interrumpir, y la mejor solucion con la de su informe.
+fn check_resumed_run(name: &str, args: &[&str], stop: usize, iterations: usize) {
+    let checkpoint = env::temp_dir().join(format!("tsl_{}_{}.ckpt", name, process::id()));
+    let checkpoint = checkpoint.to_str().unwrap();
+    let (stop, iterations) = (stop.to_string(), iterations.to_string());
+
+    let mut first_args = args.to_vec();
+    first_args.extend_from_slice(&["-n", &stop, "--checkpoint", checkpoint, "--format", "csv"]);
+    run_tsl(&first_args);
+    let mut resumed_args = args.to_vec();
+    resumed_args.extend_from_slice(&["-n", &iterations, "--resume", checkpoint, "--format", "csv"]);
+    let (trace, report) = run_tsl(&resumed_args);
+    fs::remove_file(checkpoint).unwrap();
+
+    let read_golden = |file: String| {
+        let path = golden_path(&file);
+        fs::read_to_string(&path).unwrap_or_else(|error| panic!("{}: {}", path.display(), error))
+    };
+    let expected_trace = read_golden(format!("{}.csv", name));
+    let mut lines = expected_trace.lines();
+    let header = lines.next().unwrap();
+    let tail: Vec<&str> = lines.skip_while(|line| !line.starts_with(&format!("{},", stop))).collect();
+    assert_same_trace(name, &format!("{}\n{}\n", header, tail.join("\n")), &trace);
+
+    // La configuracion de la ejecucion reanudada lleva ademas "resume".
+    let expected_report = read_golden(format!("{}.txt", name));
+    let summary = |report: &str| report[report.find("MEJOR SOLUCION").unwrap()..].to_string();
+    assert_same_trace(name, &summary(&expected_report), &summary(&report));
+}
+
+#[test]
+fn local_search_reproduces_traza_ls_2016() {
+    let (trace, _) = run_tsl(&["local-search", "TSLsimple/distancias_10.txt",
+                               "--random", "TSLsimple/aleatorios_ls_2016.txt"]);
+    // La referencia no lleva la cabecera de configuracion, que acaba con una
+    // linea vacia.
+    let configuration_end = trace.find("\n\n").expect("falta la configuracion") + 2;
+    let expected = fs::read_to_string(workspace_dir().join("TSLsimple/traza_ls_2016.txt")).unwrap();
+    assert_same_trace("traza_ls_2016.txt", &expected, &trace[configuration_end..]);
+}
+
+// Las demas reglas de pivote, sobre la misma instancia y los mismos aleatorios.
+#[test]
+fn local_search_pivot_rules() {
+    for pivot in ["best", "first", "shuffled"] {
+        check_csv_run(&format!("ls_{}", pivot), &["local-search", "TSLsimple/distancias_10.txt",
+                                                   "--random", "TSLsimple/aleatorios_ls_2016.txt",
+                                                   "--pivot", pivot]);
+    }
+}
+
+// Listas de candidatos y bits de no mirar.
+#[test]
+fn local_search_candidates() {
+    check_csv_run("ls_candidatos", &["local-search", "TSLTaboo/distancias_ts_100_2016.txt",
+                                     "--random", "TSLTaboo/aleatorios_ts_2016_caso1.txt",
+                                     "--neighbourhood", "two-opt", "--candidates", "8"]);
+}
+
+#[test]
+fn tabu_case_1() {
+    check_csv_run("tabu_caso1", &["tabu", "TSLTaboo/distancias_ts_100_2016.txt",
+                                  "--random", "TSLTaboo/aleatorios_ts_2016_caso1.txt", "--stagnation", "100",
+                                  "-n", "300"]);
+}
+
+// Interrumpida entre dos mejoras: el estancamiento se sigue contando desde la
+// ultima mejora anterior al punto de control.
+#[test]
+fn tabu_case_1_resumed() {
+    check_resumed_run("tabu_caso1", &["tabu", "TSLTaboo/distancias_ts_100_2016.txt",
+                                      "--random", "TSLTaboo/aleatorios_ts_2016_caso1.txt",
+                                      "--stagnation", "100"], 200, 300);
+}
+
+// Si no se puede escribir el punto de control la busqueda se detiene tras la
+// iteracion en que fallo, con la mejor solucion hasta ahi, y acaba en error.
+#[test]
+fn tabu_checkpoint_write_error() {
+    let checkpoint = env::temp_dir().join(format!("tsl_sin_directorio_{}", process::id())).join("tabu.ckpt");
+    let (success, trace, report) = run_tsl_status(&["tabu", "TSLTaboo/distancias_ts_100_2016.txt",
+                                                    "--random", "TSLTaboo/aleatorios_ts_2016_caso1.txt",
+                                                    "--stagnation", "100", "-n", "300",
+                                                    "--checkpoint", checkpoint.to_str().unwrap(),
+                                                    "--checkpoint-every", "10", "--format", "csv"]);
+    assert!(!success, "tsl deberia fallar al no poder escribir el punto de control");
+    let expected = fs::read_to_string(golden_path("tabu_caso1.csv")).unwrap();
+    let expected: Vec<&str> = expected.lines().take(12).collect();
+    assert_same_trace("tabu_caso1.csv", &format!("{}\n", expected.join("\n")), &trace);
+    assert!(report.contains("ERROR DE ESCRITURA\n"), "{}", report);
+    assert!(report.contains(&format!("{}: imposible escribir el fichero", checkpoint.display())), "{}", report);
+}
+
+#[test]
+fn tabu_case_2() {
+    check_csv_run("tabu_caso2", &["tabu", "TSLTaboo/distancias_ts_100_2016.txt",
+                                  "--random", "TSLTaboo/aleatorios_ts_2016_caso2.txt", "-n", "300"]);
+}
+
+#[test]
+fn tabu_case_3() {
+    check_csv_run("tabu_caso3", &["tabu", "TSLTaboo/distancias_ts_100_2016.txt",
+                                  "--random", "TSLTaboo/aleatorios_ts_2016_caso3.txt", "-n", "300"]);
+}
+
+// Con la lista tabu por defecto, mas larga que el vecindario de 10 ciudades:
+// cuando todos los movimientos son tabu se aplica el mejor.
+#[test]
+fn tabu_every_move_taboo() {
+    check_csv_run("tabu_todo_tabu", &["tabu", "TSLsimple/distancias_10.txt",
+                                      "--random", "TSLsimple/aleatorios_ls_2016.txt", "-n", "60"]);
+}
+
+// Con reinicios frecuentes para que la diversificacion consuma aleatorios.
+#[test]
+fn tabu_two_opt_case_1() {
+    check_csv_run("tabu_2opt_caso1", &["tabu-2opt", "TSLTabooKopt/distancias_ts_100_2016.txt",
+                                       "--random", "TSLTaboo/aleatorios_ts_2016_caso1.txt",
+                                       "--reboot-on-it", "20", "--stagnation", "100", "-n", "200"]);
+}
+
+// Interrumpida entre dos reinicios y sin mejorar desde la iteracion 18.
+#[test]
+fn tabu_two_opt_case_1_resumed() {
+    check_resumed_run("tabu_2opt_caso1", &["tabu-2opt", "TSLTabooKopt/distancias_ts_100_2016.txt",
+                                           "--random", "TSLTaboo/aleatorios_ts_2016_caso1.txt",
+                                           "--reboot-on-it", "20", "--stagnation", "100"], 90, 200);
+}
+
+#[test]
+fn tabu_two_opt_candidates() {
+    check_csv_run("tabu_2opt_candidatos", &["tabu-2opt", "TSLTabooKopt/distancias_ts_100_2016.txt",
+                                            "--random", "TSLTaboo/aleatorios_ts_2016_caso1.txt",
+                                            "--candidates", "8", "-n", "200"]);
+}
+
+#[test]
+fn anneal_case_1() {
+    check_csv_run("anneal_caso1", &["anneal", "TSLSAKopt/distancias_sa_100_2017.txt",
+                                    "--random", "TSLTaboo/aleatorios_ts_2016_caso1.txt", "--stagnation", "250",
+                                    "-n", "1000"]);
+}
+
+#[test]
+fn anneal_case_1_resumed() {
+    check_resumed_run("anneal_caso1", &["anneal", "TSLSAKopt/distancias_sa_100_2017.txt",
+                                        "--random", "TSLTaboo/aleatorios_ts_2016_caso1.txt",
+                                        "--stagnation", "250"], 400, 1000);
+}
+
+// Un error al grabar los aleatorios detiene la busqueda sin dar la iteracion
+// cuyo aleatorio no se pudo grabar.
+#[cfg(target_os = "linux")]
+#[test]
+fn anneal_record_random_write_error() {
+    let (success, trace, report) = run_tsl_status(&["anneal", "TSLSAKopt/distancias_sa_100_2017.txt",
+                                                    "--random", "TSLTaboo/aleatorios_ts_2016_caso1.txt",
+                                                    "--stagnation", "250", "-n", "1000",
+                                                    "--record-random", "/dev/full", "--format", "csv"]);
+    assert!(!success, "tsl deberia fallar al no poder grabar los aleatorios");
+    let expected = fs::read_to_string(golden_path("anneal_caso1.csv")).unwrap();
+    assert!(expected.starts_with(&trace), "la traza no es el principio de anneal_caso1.csv");
+    assert!(report.contains("ERROR DE ESCRITURA\n"), "{}", report);
+    assert!(report.contains("/dev/full: imposible escribir el fichero"), "{}", report);
+}
+
+#[test]
+fn anneal_case_2() {
+    check_csv_run("anneal_caso2", &["anneal", "TSLSAKopt/distancias_sa_100_2017.txt",
+                                    "--random", "TSLTaboo/aleatorios_ts_2016_caso2.txt", "-n", "1000"]);
+}
+
+#[test]
+fn anneal_case_3() {
+    check_csv_run("anneal_caso3", &["anneal", "TSLSAKopt/distancias_sa_100_2017.txt",
+                                    "--random", "TSLTaboo/aleatorios_ts_2016_caso3.txt", "-n", "1000"]);
+}
+
+// En la traza de texto, con cortes al final del recorrido.
+#[test]
+fn anneal_three_opt_text() {
+    let (trace, _) = run_tsl(&["anneal", "TSLSAKopt/distancias_sa_100_2017.txt",
+                               "--random", "TSLTaboo/aleatorios_ts_2016_caso1.txt",
+                               "--neighbourhood", "three-opt", "-n", "50"]);
+    check_golden("anneal_3opt.txt", &trace);
+}
+
+// Un fichero de aleatorios estricto que se agota termina la busqueda con la
+// mejor solucion hasta ese momento, y con error.
+#[test]
+fn anneal_strict_random_exhausted() {
+    let (success, trace, report) = run_tsl_status(&["anneal", "TSLSAKopt/distancias_sa_100_2017.txt",
+                                                    "--random", "TSLTaboo/aleatorios_ts_2016_caso1.txt",
+                                                    "--strict-random", "--format", "csv"]);
+    assert!(!success, "tsl deberia fallar al agotar los aleatorios");
+    check_golden("anneal_estricto.csv", &trace);
+    check_golden("anneal_estricto.txt", &report);
+}
+
+// Con diez ciudades la solucion inicial deja aleatorios para 45 iteraciones; la
+// traza tiene que acabar en la ultima completa.
+#[test]
+fn anneal_strict_random_exhausted_mid_run() {
+    let (success, trace, report) = run_tsl_status(&["anneal", "TSLsimple/distancias_10.txt",
+                                                    "--random", "TSLTaboo/aleatorios_ts_2016_caso1.txt",
+                                                    "--strict-random", "--format", "csv"]);
+    assert!(!success, "tsl deberia fallar al agotar los aleatorios");
+    assert!(trace.lines().last().unwrap().starts_with("45,"), "la traza no acaba en la iteracion 45");
+    check_golden("anneal_estricto_10.csv", &trace);
+    check_golden("anneal_estricto_10.txt", &report);
+}
+
+// Con dos ciudades no hay ningun movimiento: el recocido termina sin iterar
+// en lugar de fallar.
+#[test]
+fn anneal_two_cities() {
+    let instance = env::temp_dir().join(format!("tsl_dos_ciudades_{}.tsp", process::id()));
+    fs::write(&instance, "NAME: dos\nTYPE: TSP\nDIMENSION: 2\nEDGE_WEIGHT_TYPE: EUC_2D\n\
+                          NODE_COORD_SECTION\n1 0 0\n2 3 4\nEOF\n").unwrap();
+    let (trace, report) = run_tsl(&["anneal", instance.to_str().unwrap(), "-s", "1", "--iterations", "5",
+                                    "--format", "csv"]);
+    fs::remove_file(&instance).unwrap();
+
+    let rows: Vec<&str> = trace.lines().skip(1).collect();
+    assert_eq!(rows, ["0,,,,10,10,0.28036732520571284,,false,false"]);
+    assert!(report.contains("FUNCION OBJETIVO (km): 10\n\tITERACION: 0\n"), "{}", report);
+}
+
+// Las perturbaciones necesitan al menos dos ciudades ademas del origen.
+#[test]
+fn iterated_local_search_two_cities() {
+    let instance = env::temp_dir().join(format!("tsl_dos_ciudades_ils_{}.tsp", process::id()));
+    fs::write(&instance, "NAME: dos\nTYPE: TSP\nDIMENSION: 2\nEDGE_WEIGHT_TYPE: EUC_2D\n\
+                          NODE_COORD_SECTION\n1 0 0\n2 3 4\nEOF\n").unwrap();
+    let (success, _, report) = run_tsl_status(&["iterated-local-search", instance.to_str().unwrap(), "-s", "1"]);
+    fs::remove_file(&instance).unwrap();
+
+    assert!(!success);
+    assert!(report.contains(&format!("{}: el algoritmo necesita al menos 3 ciudades y la instancia tiene 2",
+                                     instance.display())), "{}", report);
+}
+
+#[test]
+fn lin_kernighan_case_1() {
+    check_csv_run("lin_kernighan_caso1", &["lin-kernighan", "TSLTaboo/distancias_ts_100_2016.txt",
+                                           "--random", "TSLTaboo/aleatorios_ts_2016_caso1.txt"]);
+}
+
+// Con reinicios frecuentes para que tambien se vean en la traza.
+#[test]
+fn iterated_local_search_case_1() {
+    check_csv_run("ils_caso1", &["iterated-local-search", "TSLTaboo/distancias_ts_100_2016.txt",
+                                 "--random", "TSLTaboo/aleatorios_ts_2016_caso1.txt",
+                                 "--acceptance", "restart", "--restart-after", "20", "-n", "200"]);
+}
+
+#[test]
+fn iterated_local_search_lin_kernighan() {
+    check_csv_run("ils_lin_kernighan", &["iterated-local-search", "TSLTaboo/distancias_ts_100_2016.txt",
+                                         "--random", "TSLTaboo/aleatorios_ts_2016_caso1.txt",
+                                         "--local-search", "lin-kernighan", "-n", "50"]);
+}
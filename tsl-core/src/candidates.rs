@@ -0,0 +1,224 @@
+use std::*;
+use std::collections::VecDeque;
+
+use crate::neighbourhood::Move;
+use crate::neighbourhood::Neighbourhood;
+use crate::neighbourhood::OR_OPT_MAX_LENGTH;
+use crate::tour;
+use crate::triangular::TriangularMatrix;
+
+// Listas de candidatos: para cada ciudad, las mas cercanas, de la mas cercana
+// a la mas lejana y, a igual distancia, por numero de ciudad. En un buen
+// recorrido casi todas las aristas van a una ciudad cercana, asi que basta con
+// probar los movimientos que ponen una ciudad junto a una de sus candidatas.
+pub struct CandidateLists {
+    neighbours: Vec<Vec<usize>>,
+}
+
+impl CandidateLists {
+    // O(n^2): se miran todas las distancias, pero solo se ordenan las size
+    // menores de cada ciudad.
+    pub fn new(cost_map: &TriangularMatrix<usize>, size: usize) -> CandidateLists {
+        let nodes = cost_map.number_of_lines;
+        let neighbours = (0..nodes)
+            .map(|node| {
+                let key = |&other: &usize| (tour::distance(cost_map, node, other), other);
+                let mut others: Vec<usize> = (0..nodes).filter(|&other| other != node).collect();
+                if size < others.len() {
+                    others.select_nth_unstable_by_key(size, key);
+                    others.truncate(size);
+                }
+                others.sort_unstable_by_key(key);
+                others
+            })
+            .collect();
+        CandidateLists { neighbours }
+    }
+
+    pub fn of(&self, node: usize) -> &[usize] {
+        &self.neighbours[node]
+    }
+
+    // Solo two-opt y or-opt tienen movimientos definidos por la arista que
+    // anaden.
+    pub fn supports(neighbourhood: Neighbourhood) -> bool {
+        matches!(neighbourhood, Neighbourhood::TwoOpt | Neighbourhood::OrOpt)
+    }
+
+    // Movimientos del vecindario que dejan node junto a una de sus candidatas.
+    // positions es la posicion de cada ciudad en la solucion, como la deja
+    // positions().
+    pub fn moves_around(&self, neighbourhood: Neighbourhood, solution: &[usize], positions: &[usize],
+                        node: usize) -> Vec<Move> {
+        let mut moves = Vec::new();
+        for &candidate in self.neighbours[node].iter() {
+            match neighbourhood {
+                Neighbourhood::TwoOpt => two_opt_moves(solution, positions, node, candidate, &mut moves),
+                Neighbourhood::OrOpt => or_opt_moves(solution, positions, node, candidate, &mut moves),
+                _ => panic!("el vecindario {} no admite listas de candidatos", neighbourhood),
+            }
+        }
+        moves
+    }
+
+    // Los de todas las ciudades, origen incluido; un movimiento puede salir
+    // dos veces, una por cada ciudad de la arista que anade.
+    pub fn moves<'a>(&'a self, neighbourhood: Neighbourhood, solution: &'a [usize], positions: &'a [usize])
+                     -> impl Iterator<Item = Move> + 'a {
+        (0..self.neighbours.len())
+            .flat_map(move |node| self.moves_around(neighbourhood, solution, positions, node))
+    }
+}
+
+// Posicion de cada ciudad en la solucion; la del origen no se usa.
+pub fn positions(solution: &[usize]) -> Vec<usize> {
+    let mut positions = vec![0; solution.len() + 1];
+    for (position, &node) in solution.iter().enumerate() {
+        positions[node] = position;
+    }
+    positions
+}
+
+// Pone al dia las posiciones despues de aplicar el movimiento a la solucion.
+pub fn update_positions(positions: &mut [usize], solution: &[usize], movement: &Move) {
+    let (first, last) = match *movement {
+        Move::Swap(i, j) | Move::Reversal(i, j) | Move::Insertion(i, j) => (cmp::min(i, j), cmp::max(i, j)),
+        Move::OrOpt { from, length, to, .. } => (cmp::min(from, to), cmp::max(from, to) + length - 1),
+        Move::ThreeOpt { i, k, .. } => (i, k - 1),
+    };
+    for position in first..=last {
+        positions[solution[position]] = position;
+    }
+}
+
+// Posicion en el ciclo que empieza en el origen: la del origen es 0 y la de
+// las demas, su posicion en la solucion mas uno.
+fn cycle_position(positions: &[usize], node: usize) -> usize {
+    if node == 0 { 0 } else { positions[node] + 1 }
+}
+
+// Inversion del tramo [first, last] del ciclo. Si el tramo pasa por el origen
+// se invierte el resto del ciclo, que deja el mismo recorrido.
+fn cycle_reversal(cycle_len: usize, first: usize, last: usize) -> Option<Move> {
+    let (first, last) = if first == 0 { (last, cycle_len - 2) } else { (first - 1, last - 1) };
+    if first < last { Some(Move::Reversal(last, first)) } else { None }
+}
+
+// Los dos 2-opt que anaden la arista (a, c): quitando las aristas que salen de
+// a y de c hacia delante, o hacia atras.
+fn two_opt_moves(solution: &[usize], positions: &[usize], a: usize, c: usize, moves: &mut Vec<Move>) {
+    let cycle_len = solution.len() + 1;
+    let (a_position, c_position) = (cycle_position(positions, a), cycle_position(positions, c));
+    let (next, previous) = ((a_position + 1) % cycle_len, (a_position + cycle_len - 1) % cycle_len);
+    if c_position == next || c_position == previous {
+        return;
+    }
+    let (forward, backward) = if a_position < c_position {
+        ((a_position + 1, c_position), (a_position, c_position - 1))
+    } else {
+        ((c_position + 1, a_position), (c_position, a_position - 1))
+    };
+    moves.extend(cycle_reversal(cycle_len, forward.0, forward.1));
+    moves.extend(cycle_reversal(cycle_len, backward.0, backward.1));
+}
+
+// Los Or-opt que llevan un tramo con a en un extremo junto a c, a un lado u
+// otro de c, con a del lado de c.
+fn or_opt_moves(solution: &[usize], positions: &[usize], a: usize, c: usize, moves: &mut Vec<Move>) {
+    if a == 0 {
+        return;
+    }
+    let solution_size = solution.len();
+    let a_position = positions[a];
+    if c == tour::previous_node(solution, a_position) || c == tour::next_node(solution, a_position) {
+        return;
+    }
+    for length in 1..=cmp::min(OR_OPT_MAX_LENGTH, solution_size - 1) {
+        let starts = [Some(a_position), (a_position + 1).checked_sub(length)];
+        for (index, from) in starts.into_iter().enumerate() {
+            let Some(from) = from else { continue };
+            if (index == 1 && length == 1) || from + length > solution_size {
+                continue;
+            }
+            // Posicion de c en la solucion sin el tramo.
+            let rest_position = match c {
+                0 => None,
+                c if positions[c] < from => Some(positions[c]),
+                c if positions[c] < from + length => continue,
+                c => Some(positions[c] - length),
+            };
+            let after = rest_position.map_or(0, |position| position + 1);
+            let before = rest_position.unwrap_or(solution_size - length);
+            for (to, reversed) in [(after, from != a_position), (before, from + length - 1 != a_position)] {
+                if to != from {
+                    moves.push(Move::OrOpt { from, length, to, reversed });
+                }
+            }
+        }
+    }
+}
+
+// Bits de no mirar: una ciudad solo se vuelve a mirar si cambia alguna de sus
+// aristas. Las ciudades por mirar esperan en una cola, cada una una sola vez.
+pub struct DontLookBits {
+    queue: VecDeque<usize>,
+    queued: Vec<bool>,
+}
+
+impl DontLookBits {
+    pub fn new(order: impl IntoIterator<Item = usize>, nodes: usize) -> DontLookBits {
+        let mut dont_look_bits = DontLookBits { queue: VecDeque::new(), queued: vec![false; nodes] };
+        for node in order {
+            dont_look_bits.wake(node);
+        }
+        dont_look_bits
+    }
+
+    // La siguiente ciudad por mirar, que deja de estarlo.
+    pub fn pop(&mut self) -> Option<usize> {
+        let node = self.queue.pop_front()?;
+        self.queued[node] = false;
+        Some(node)
+    }
+
+    pub fn wake(&mut self, node: usize) {
+        if !self.queued[node] {
+            self.queued[node] = true;
+            self.queue.push_back(node);
+        }
+    }
+
+    // Despierta las ciudades de las aristas que quita el movimiento; hay que
+    // llamarla antes de aplicarlo.
+    pub fn wake_endpoints(&mut self, movement: &Move, solution: &[usize]) {
+        let previous = |i| tour::previous_node(solution, i);
+        let next = |i| tour::next_node(solution, i);
+        let endpoints = match *movement {
+            Move::Swap(i, j) => vec![previous(i), solution[i], next(i), previous(j), solution[j], next(j)],
+            Move::Reversal(i, j) => {
+                let (first, last) = (cmp::min(i, j), cmp::max(i, j));
+                vec![previous(first), solution[first], solution[last], next(last)]
+            }
+            Move::Insertion(from, to) => segment_endpoints(solution, from, 1, to),
+            Move::OrOpt { from, length, to, .. } => segment_endpoints(solution, from, length, to),
+            Move::ThreeOpt { i, j, k, .. } =>
+                vec![previous(i), solution[i], solution[j - 1], solution[j], solution[k - 1], next(k - 1)],
+        };
+        for node in endpoints {
+            self.wake(node);
+        }
+    }
+}
+
+// Los extremos del tramo, sus vecinos y las dos ciudades entre las que entra.
+fn segment_endpoints(solution: &[usize], from: usize, length: usize, to: usize) -> Vec<usize> {
+    let last = from + length - 1;
+    // Ciudad de la posicion i de la solucion sin el tramo, con el origen antes y despues.
+    let rest = |i: Option<usize>| match i {
+        Some(i) if i < from => solution[i],
+        Some(i) if i + length < solution.len() => solution[i + length],
+        _ => 0,
+    };
+    vec![tour::previous_node(solution, from), solution[from], solution[last], tour::next_node(solution, last),
+         rest(to.checked_sub(1)), rest(Some(to))]
+}
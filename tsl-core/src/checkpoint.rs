@@ -0,0 +1,237 @@
+use std::*;
+use std::io::Write;
+use std::str::FromStr;
+
+use crate::config::ConfigError;
+use crate::config::Configurable;
+use crate::config::ExperimentFile;
+use crate::error::WriteFailure;
+use crate::neighbourhood::Move;
+use crate::observer::Observer;
+use crate::random_generator::RandomGenerator;
+use crate::solver::Solver;
+use crate::stopping::LastImprovement;
+
+// Estado completo de una busqueda para reanudarla mas tarde. Se guarda con la
+// sintaxis de los ficheros de experimento: el estado como claves globales y los
+// parametros en la seccion del algoritmo. Las listas van separadas por espacios
+// y los pares como "i,j".
+pub struct Checkpoint {
+    file_name: String,
+    algorithm: String,
+    state: Vec<(String, String, usize)>,
+    parameters: Vec<(String, String, usize)>,
+}
+
+impl Checkpoint {
+    pub fn new(algorithm: &str) -> Checkpoint {
+        Checkpoint {
+            file_name: String::new(),
+            algorithm: algorithm.to_string(),
+            state: Vec::new(),
+            parameters: Vec::new(),
+        }
+    }
+
+    pub fn set(&mut self, key: &str, value: impl fmt::Display) {
+        self.state.push((key.to_string(), value.to_string(), 0));
+    }
+
+    pub fn set_list<T: fmt::Display>(&mut self, key: &str, values: impl IntoIterator<Item = T>) {
+        let values: Vec<String> = values.into_iter().map(|value| value.to_string()).collect();
+        self.set(key, values.join(" "));
+    }
+
+    pub fn set_pairs(&mut self, key: &str, pairs: impl IntoIterator<Item = (usize, usize)>) {
+        self.set_list(key, pairs.into_iter().map(|(i, j)| format!("{},{}", i, j)));
+    }
+
+    // parameters se escribe con el Display de los parametros, una clave por linea.
+    pub fn set_parameters(&mut self, parameters: &dyn fmt::Display) {
+        self.parameters = parameters.to_string().lines()
+            .filter_map(|line| line.split_once('='))
+            .map(|(key, value)| (key.trim().to_string(), value.trim().to_string(), 0))
+            .collect();
+    }
+
+    // Se escribe en un fichero temporal y se renombra para no dejar nunca un
+    // punto de control a medias.
+    pub fn write(&self, file_name: &str, iteration: usize) -> io::Result<()> {
+        let temporary_file_name = format!("{}.tmp", file_name);
+        {
+            let mut writer = io::BufWriter::new(fs::File::create(&temporary_file_name)?);
+            writeln!(writer, "# Punto de control tras la iteracion {}", iteration)?;
+            writeln!(writer, "algorithm = \"{}\"", self.algorithm)?;
+            for (key, value, _) in self.state.iter() {
+                writeln!(writer, "{} = {}", key, value)?;
+            }
+            writeln!(writer, "[{}]", self.algorithm)?;
+            for (key, value, _) in self.parameters.iter() {
+                writeln!(writer, "{} = {}", key, value)?;
+            }
+            writer.flush()?;
+        }
+        fs::rename(&temporary_file_name, file_name)
+    }
+
+    pub fn from_file(file_name: &str, algorithm: &str) -> Result<Checkpoint, ConfigError> {
+        let experiment = ExperimentFile::from_file(file_name)?;
+        let mut checkpoint = Checkpoint::new(algorithm);
+        checkpoint.file_name = file_name.to_string();
+
+        let mut saved_algorithm = None;
+        for entry in experiment.entries_for(algorithm) {
+            let value = (entry.key.clone(), entry.value.clone(), entry.line);
+            match entry.section {
+                Some(_) => checkpoint.parameters.push(value),
+                None if entry.key == "algorithm" => saved_algorithm = Some(entry.value.clone()),
+                None => checkpoint.state.push(value),
+            }
+        }
+        match saved_algorithm {
+            Some(ref saved) if saved == algorithm => Ok(checkpoint),
+            Some(saved) => Err(checkpoint.error_at_line(0, format!("el punto de control es de {}, no de {}",
+                                                           saved, algorithm))),
+            None => Err(checkpoint.error_at_line(0, "falta algorithm".to_string())),
+        }
+    }
+
+    pub fn apply_parameters(&self, parameters: &mut dyn Configurable) -> Result<(), ConfigError> {
+        for (key, value, line) in self.parameters.iter() {
+            parameters.set(key, value).map_err(|message| self.error_at_line(*line, message))?;
+        }
+        Ok(())
+    }
+
+    fn error_at_line(&self, line: usize, message: String) -> ConfigError {
+        ConfigError { file_name: self.file_name.clone(), line, message }
+    }
+
+    // Error en el valor de key, para las comprobaciones de cada algoritmo.
+    pub fn error(&self, key: &str, message: String) -> ConfigError {
+        let line = self.entry(key).map_or(0, |(_, line)| line);
+        self.error_at_line(line, message)
+    }
+
+    fn entry(&self, key: &str) -> Result<(&str, usize), ConfigError> {
+        self.state.iter()
+            .find(|(saved_key, _, _)| saved_key == key)
+            .map(|(_, value, line)| (value.as_str(), *line))
+            .ok_or_else(|| self.error_at_line(0, format!("falta {}", key)))
+    }
+
+    pub fn get<T: FromStr>(&self, key: &str) -> Result<T, ConfigError> {
+        let (value, line) = self.entry(key)?;
+        value.parse::<T>()
+            .map_err(|_| self.error_at_line(line, format!("valor no valido para {}: {}", key, value)))
+    }
+
+    pub fn get_list<T: FromStr>(&self, key: &str) -> Result<Vec<T>, ConfigError> {
+        let (value, line) = self.entry(key)?;
+        value.split_whitespace()
+            .map(|token| token.parse::<T>()
+                .map_err(|_| self.error_at_line(line, format!("valor no valido para {}: {}", key, token))))
+            .collect()
+    }
+
+    pub fn get_pairs(&self, key: &str) -> Result<Vec<(usize, usize)>, ConfigError> {
+        let (value, line) = self.entry(key)?;
+        value.split_whitespace()
+            .map(|token| token.split_once(',')
+                .and_then(|(i, j)| Some((i.parse().ok()?, j.parse().ok()?)))
+                .ok_or_else(|| self.error_at_line(line, format!("valor no valido para {}: {}", key, token))))
+            .collect()
+    }
+
+    pub fn set_random(&mut self, rand_gen: &dyn RandomGenerator) {
+        if let Some(state) = rand_gen.state() {
+            self.set("random_state", state);
+        }
+    }
+
+    pub fn restore_random(&self, rand_gen: &mut dyn RandomGenerator) -> Result<(), ConfigError> {
+        let (state, line) = self.entry("random_state")?;
+        rand_gen.restore_state(state).map_err(|message| self.error_at_line(line, message))
+    }
+
+    // Una solucion tiene que ser una permutacion de 1..=solution_size.
+    pub fn get_tour(&self, key: &str, solution_size: usize) -> Result<Vec<usize>, ConfigError> {
+        let tour = self.get_list::<usize>(key)?;
+        let mut seen = vec![false; solution_size + 1];
+        let is_tour = tour.len() == solution_size
+            && tour.iter().all(|&node| (1..=solution_size).contains(&node) && !mem::replace(&mut seen[node], true));
+        if !is_tour {
+            return Err(self.error(key, format!("{} no es un recorrido de {} ciudades", key, solution_size + 1)));
+        }
+        Ok(tour)
+    }
+
+    // Movimiento guardado con Move::code.
+    pub fn get_move(&self, key: &str, solution_size: usize) -> Result<Move, ConfigError> {
+        let (value, line) = self.entry(key)?;
+        Move::from_code(value, solution_size)
+            .ok_or_else(|| self.error_at_line(line, format!("valor no valido para {}: {}", key, value)))
+    }
+}
+
+// Observador que guarda un punto de control cada every iteraciones y al terminar.
+// Ademas del estado del algoritmo guarda, como best_cost_iteration, la ultima
+// iteracion que mejoro la mejor solucion, para el criterio de estancamiento.
+// Los errores de escritura quedan en failure.
+pub struct CheckpointWriter<S> {
+    save_state: fn(&S, &mut Checkpoint),
+    algorithm: String,
+    file_name: String,
+    every: usize,
+    failure: WriteFailure,
+    last_improvement: LastImprovement,
+    resumed_improvement: Option<usize>,
+}
+
+impl<S: Solver> CheckpointWriter<S> {
+    pub fn new(save_state: fn(&S, &mut Checkpoint), algorithm: &str, file_name: &str, every: usize,
+               failure: WriteFailure) -> CheckpointWriter<S> {
+        CheckpointWriter {
+            save_state,
+            algorithm: algorithm.to_string(),
+            file_name: file_name.to_string(),
+            every,
+            failure,
+            last_improvement: LastImprovement::default(),
+            resumed_improvement: None,
+        }
+    }
+
+    // Al reanudar, la iteracion de la ultima mejora del punto de control leido.
+    pub fn with_last_improvement(mut self, iteration: usize) -> CheckpointWriter<S> {
+        self.resumed_improvement = Some(iteration);
+        self
+    }
+
+    fn write(&self, solver: &S) {
+        let mut checkpoint = Checkpoint::new(&self.algorithm);
+        (self.save_state)(solver, &mut checkpoint);
+        checkpoint.set("best_cost_iteration", self.last_improvement.iteration());
+        if let Err(error) = checkpoint.write(&self.file_name, solver.iteration()) {
+            self.failure.set(&self.file_name, error);
+        }
+    }
+}
+
+impl<S: Solver> Observer<S> for CheckpointWriter<S> {
+    fn on_start(&mut self, solver: &S) {
+        self.last_improvement = LastImprovement::new(solver, self.resumed_improvement);
+    }
+
+    fn on_iteration(&mut self, solver: &S) {
+        self.last_improvement.update(solver);
+        if self.every > 0 && solver.iteration().is_multiple_of(self.every) {
+            self.write(solver);
+        }
+    }
+
+    fn on_finish(&mut self, solver: &S) {
+        self.last_improvement.update(solver);
+        self.write(solver);
+    }
+}
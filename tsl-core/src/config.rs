@@ -0,0 +1,117 @@
+use std::*;
+use std::fs::File;
+use std::io::prelude::*;
+use std::str::FromStr;
+
+// Ficheros de experimento con un subconjunto de TOML: lineas "clave = valor",
+// comentarios con '#' y secciones "[algoritmo]" con los parametros propios de
+// cada algoritmo.
+pub struct ExperimentFile {
+    file_name: String,
+    entries: Vec<Entry>,
+}
+
+pub struct Entry {
+    pub section: Option<String>,
+    pub key: String,
+    pub value: String,
+    pub line: usize,
+}
+
+#[derive(Debug)]
+pub struct ConfigError {
+    pub file_name: String,
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.line == 0 {
+            write!(f, "{}: {}", self.file_name, self.message)
+        } else {
+            write!(f, "{}:{}: {}", self.file_name, self.line, self.message)
+        }
+    }
+}
+
+impl error::Error for ConfigError {}
+
+pub trait Configurable {
+    fn set(&mut self, key: &str, value: &str) -> Result<(), String>;
+}
+
+impl ExperimentFile {
+    pub fn from_file(file_name: &str) -> Result<ExperimentFile, ConfigError> {
+        let error = |line: usize, message: String| ConfigError {
+            file_name: file_name.to_string(),
+            line,
+            message,
+        };
+
+        let mut file_content = String::new();
+        File::open(file_name)
+            .and_then(|mut file| file.read_to_string(&mut file_content))
+            .map_err(|e| error(0, format!("imposible leer el fichero de experimento: {}", e)))?;
+
+        let mut section = None;
+        let mut entries = Vec::new();
+        for (number, line) in file_content.lines().enumerate() {
+            let line = match line.find('#') {
+                Some(comment) => &line[..comment],
+                None => line,
+            }.trim();
+
+            if line.is_empty() {
+                continue;
+            }
+            if line.starts_with('[') && line.ends_with(']') {
+                section = Some(line[1..line.len() - 1].trim().to_string());
+                continue;
+            }
+            match line.find('=') {
+                Some(equals) => entries.push(Entry {
+                    section: section.clone(),
+                    key: line[..equals].trim().to_string(),
+                    value: line[equals + 1..].trim().trim_matches('"').to_string(),
+                    line: number + 1,
+                }),
+                None => return Err(error(number + 1, format!("se esperaba \"clave = valor\": {}", line))),
+            }
+        }
+
+        Ok(ExperimentFile { file_name: file_name.to_string(), entries })
+    }
+
+    // Entradas globales y de la seccion indicada, en el orden del fichero.
+    pub fn entries_for<'a>(&'a self, section: &'a str) -> impl Iterator<Item = &'a Entry> + 'a {
+        self.entries.iter()
+            .filter(move |e| e.section.as_ref().is_none_or(|s| s == section))
+    }
+
+    pub fn error(&self, entry: &Entry, message: String) -> ConfigError {
+        ConfigError {
+            file_name: self.file_name.clone(),
+            line: entry.line,
+            message,
+        }
+    }
+}
+
+pub fn parse_value<T: FromStr>(key: &str, value: &str) -> Result<T, String> {
+    value.parse::<T>()
+        .map_err(|_| format!("valor no valido para {}: {}", key, value))
+}
+
+// Para los parametros con los que 0 no tiene sentido: tamanos, divisores y
+// numeros de intentos.
+pub fn parse_positive(key: &str, value: &str) -> Result<usize, String> {
+    match parse_value(key, value)? {
+        0 => Err(format!("valor no valido para {}: tiene que ser mayor que 0", key)),
+        value => Ok(value),
+    }
+}
+
+pub fn unknown_key(key: &str) -> String {
+    format!("parametro desconocido: {}", key)
+}
@@ -0,0 +1,175 @@
+use std::*;
+
+use crate::error;
+use crate::error::LoadError;
+use crate::triangular::TriangularMatrix;
+
+// Funciones de distancia de TSPLIB, con sus reglas de redondeo a entero.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DistanceFunction {
+    Euc2d,
+    Ceil2d,
+    Att,
+    Geo,
+}
+
+impl DistanceFunction {
+    pub fn from_name(name: &str) -> Option<DistanceFunction> {
+        match name {
+            "EUC_2D" => Some(DistanceFunction::Euc2d),
+            "CEIL_2D" => Some(DistanceFunction::Ceil2d),
+            "ATT" => Some(DistanceFunction::Att),
+            "GEO" => Some(DistanceFunction::Geo),
+            _ => None,
+        }
+    }
+
+    pub fn distance(&self, from: (f64, f64), to: (f64, f64)) -> usize {
+        let dx = from.0 - to.0;
+        let dy = from.1 - to.1;
+        match *self {
+            DistanceFunction::Euc2d => nint((dx * dx + dy * dy).sqrt()),
+            DistanceFunction::Ceil2d => (dx * dx + dy * dy).sqrt().ceil() as usize,
+            DistanceFunction::Att => {
+                let r = ((dx * dx + dy * dy) / 10.0).sqrt();
+                let t = nint(r);
+                if (t as f64) < r { t + 1 } else { t }
+            },
+            DistanceFunction::Geo => {
+                const RRR: f64 = 6378.388;
+                let (from_latitude, from_longitude) = (geo_radians(from.0), geo_radians(from.1));
+                let (to_latitude, to_longitude) = (geo_radians(to.0), geo_radians(to.1));
+                let q1 = (from_longitude - to_longitude).cos();
+                let q2 = (from_latitude - to_latitude).cos();
+                let q3 = (from_latitude + to_latitude).cos();
+                (RRR * (0.5 * ((1.0 + q1) * q2 - (1.0 - q1) * q3)).acos() + 1.0) as usize
+            },
+        }
+    }
+}
+
+fn nint(x: f64) -> usize {
+    (x + 0.5) as usize
+}
+
+// Grados.minutos a radianes, con el valor de PI que fija TSPLIB.
+#[allow(clippy::approx_constant)]
+fn geo_radians(x: f64) -> f64 {
+    const PI: f64 = 3.141592;
+    let degrees = x.trunc();
+    let minutes = x - degrees;
+    PI * (degrees + 5.0 * minutes / 3.0) / 180.0
+}
+
+pub fn cost_map(coordinates: &[(f64, f64)], distance: DistanceFunction) -> TriangularMatrix<usize> {
+    let mut cost_map = TriangularMatrix::<usize>::filled_zeros(coordinates.len());
+    for i in 1..coordinates.len() {
+        for j in 0..i {
+            cost_map.set(i, j, distance.distance(coordinates[i], coordinates[j]));
+        }
+    }
+    cost_map
+}
+
+// Un fichero de coordenadas tiene una linea "x y" por ciudad; la primera es el
+// origen.
+pub fn is_coordinate_file(file_content: &str) -> bool {
+    file_content.lines()
+        .find(|line| !line.trim().is_empty())
+        .is_some_and(|line| error::tokens(line).count() == 2)
+}
+
+pub fn parse(file_name: &str, file_content: &str) -> Result<TriangularMatrix<usize>, LoadError> {
+    let mut coordinates = Vec::new();
+    for (number, line) in file_content.lines().enumerate() {
+        let tokens: Vec<(usize, &str)> = error::tokens(line).collect();
+        if tokens.is_empty() {
+            continue;
+        }
+        if tokens.len() != 2 {
+            return Err(LoadError::RowLength {
+                file_name: file_name.to_string(),
+                line: number + 1,
+                expected: 2,
+                found: tokens.len(),
+            });
+        }
+        let x = error::parse_token::<f64>(file_name, number + 1, tokens[0].0, tokens[0].1)?;
+        let y = error::parse_token::<f64>(file_name, number + 1, tokens[1].0, tokens[1].1)?;
+        coordinates.push((x, y));
+    }
+    if coordinates.len() < 2 {
+        return Err(LoadError::Empty { file_name: file_name.to_string() });
+    }
+
+    Ok(cost_map(&coordinates, DistanceFunction::Euc2d))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tour;
+
+    #[test]
+    fn euc_2d_rounds_to_the_nearest_integer() {
+        let distance = |to| DistanceFunction::Euc2d.distance((0.0, 0.0), to);
+        assert_eq!(distance((3.0, 4.0)), 5);
+        assert_eq!(distance((1.0, 1.0)), 1);
+        assert_eq!(distance((2.0, 3.0)), 4);
+        assert_eq!(distance((0.0, 2.5)), 3);
+        // Los dos primeros nodos de berlin52.
+        assert_eq!(DistanceFunction::Euc2d.distance((565.0, 575.0), (25.0, 185.0)), 666);
+    }
+
+    #[test]
+    fn ceil_2d_rounds_up() {
+        let distance = |to| DistanceFunction::Ceil2d.distance((0.0, 0.0), to);
+        assert_eq!(distance((3.0, 4.0)), 5);
+        assert_eq!(distance((1.0, 1.0)), 2);
+        assert_eq!(distance((0.0, 0.1)), 1);
+        assert_eq!(distance((0.0, 2.5)), 3);
+    }
+
+    // Pseudoeuclidea: raiz de la suma de cuadrados entre 10, y un km mas cuando
+    // el redondeo se queda por debajo.
+    #[test]
+    fn att_pseudo_euclidean_distance() {
+        let distance = |to| DistanceFunction::Att.distance((0.0, 0.0), to);
+        assert_eq!(distance((10.0, 0.0)), 4);
+        assert_eq!(distance((0.0, 40.0)), 13);
+        assert_eq!(distance((30.0, 40.0)), 16);
+        assert_eq!(distance((0.0, 0.0)), 0);
+        // Los dos primeros nodos de att48: r = 1494.7.
+        assert_eq!(DistanceFunction::Att.distance((6734.0, 1453.0), (2233.0, 10.0)), 1495);
+    }
+
+    // Grados.minutos sobre la esfera de radio 6378.388 km, truncado tras sumar 1.
+    #[test]
+    fn geo_uses_degrees_and_minutes() {
+        let distance = |from, to| DistanceFunction::Geo.distance(from, to);
+        assert_eq!(distance((16.47, 96.10), (16.47, 94.44)), 153);
+        assert_eq!(distance((16.47, 96.10), (20.09, 92.54)), 510);
+        assert_eq!(distance((0.0, 0.0), (0.0, 0.0)), 1);
+        // 0.30 son treinta minutos, medio grado.
+        assert_eq!(distance((0.0, 0.0), (0.30, 0.0)), distance((0.0, 0.0), (0.0, 0.30)));
+    }
+
+    #[test]
+    fn coordinate_files() {
+        let file_content = "0 0\n\n3 0\n 0 4 \n";
+        assert!(is_coordinate_file(file_content));
+        let cost_map = parse("ciudades.txt", file_content).unwrap();
+        assert_eq!(cost_map.number_of_lines, 3);
+        assert_eq!((tour::distance(&cost_map, 0, 1), tour::distance(&cost_map, 0, 2),
+                    tour::distance(&cost_map, 1, 2)), (3, 4, 5));
+        assert!(!is_coordinate_file("5\n3 4\n"));
+    }
+
+    #[test]
+    fn malformed_coordinate_files() {
+        let error = |file_content| parse("ciudades.txt", file_content).err().expect("se esperaba un error").to_string();
+        assert_eq!(error("0 0\n1 2 3\n"), "ciudades.txt:2: se esperaban 2 valores en la linea y hay 3");
+        assert_eq!(error("0 0\n1 2\n1  y\n"), "ciudades.txt:3:4: valor no valido: y");
+        assert_eq!(error("0 0\n"), "ciudades.txt: el fichero no contiene datos");
+    }
+}
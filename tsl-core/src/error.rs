@@ -0,0 +1,88 @@
+use std::*;
+use std::sync::Arc;
+use std::sync::Mutex;
+
+// Las lineas y columnas empiezan en 1, como en los editores.
+#[derive(Debug)]
+pub enum LoadError {
+    Io { file_name: String, error: io::Error },
+    Parse { file_name: String, line: usize, column: usize, token: String },
+    RowLength { file_name: String, line: usize, expected: usize, found: usize },
+    Format { file_name: String, line: usize, message: String },
+    Empty { file_name: String },
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            LoadError::Io { ref file_name, ref error } =>
+                write!(f, "{}: imposible leer el fichero: {}", file_name, error),
+            LoadError::Parse { ref file_name, line, column, ref token } =>
+                write!(f, "{}:{}:{}: valor no valido: {}", file_name, line, column, token),
+            LoadError::RowLength { ref file_name, line, expected, found } =>
+                write!(f, "{}:{}: se esperaban {} valores en la linea y hay {}",
+                       file_name, line, expected, found),
+            LoadError::Format { ref file_name, line: 0, ref message } =>
+                write!(f, "{}: {}", file_name, message),
+            LoadError::Format { ref file_name, line, ref message } =>
+                write!(f, "{}:{}: {}", file_name, line, message),
+            LoadError::Empty { ref file_name } =>
+                write!(f, "{}: el fichero no contiene datos", file_name),
+        }
+    }
+}
+
+impl error::Error for LoadError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            LoadError::Io { ref error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+// Primer error al escribir un fichero durante la busqueda: los puntos de
+// control o la grabacion de los aleatorios. Quien escribe no puede devolverlo,
+// asi que lo deja aqui; la busqueda se detiene y el programa lo da al acabar.
+#[derive(Clone, Default)]
+pub struct WriteFailure {
+    message: Arc<Mutex<Option<String>>>,
+}
+
+impl WriteFailure {
+    pub fn new() -> WriteFailure {
+        WriteFailure::default()
+    }
+
+    pub fn set(&self, file_name: &str, error: io::Error) {
+        let mut message = self.message.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        message.get_or_insert_with(|| format!("{}: imposible escribir el fichero: {}", file_name, error));
+    }
+
+    pub fn error(&self) -> Option<String> {
+        self.message.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).clone()
+    }
+}
+
+pub fn read_file(file_name: &str) -> Result<String, LoadError> {
+    fs::read_to_string(file_name).map_err(|error| LoadError::Io {
+        file_name: file_name.to_string(),
+        error,
+    })
+}
+
+// Separa una linea en valores junto a la columna en la que empieza cada uno.
+pub fn tokens(line: &str) -> impl Iterator<Item = (usize, &str)> {
+    line.split_whitespace()
+        .map(move |token| (token.as_ptr() as usize - line.as_ptr() as usize + 1, token))
+}
+
+pub fn parse_token<T: str::FromStr>(file_name: &str, line: usize, column: usize, token: &str)
+                                    -> Result<T, LoadError> {
+    token.parse::<T>().map_err(|_| LoadError::Parse {
+        file_name: file_name.to_string(),
+        line,
+        column,
+        token: token.to_string(),
+    })
+}
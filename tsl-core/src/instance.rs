@@ -0,0 +1,18 @@
+use crate::coordinates;
+use crate::error;
+use crate::error::LoadError;
+use crate::triangular::TriangularMatrix;
+use crate::tsplib;
+
+// Carga una instancia en cualquiera de los formatos soportados, decidiendo por
+// el contenido y no por la extension.
+pub fn from_file(file_name: &str) -> Result<TriangularMatrix<usize>, LoadError> {
+    let file_content = error::read_file(file_name)?;
+    if tsplib::is_tsplib(&file_content) {
+        tsplib::parse(file_name, &file_content)
+    } else if coordinates::is_coordinate_file(&file_content) {
+        coordinates::parse(file_name, &file_content)
+    } else {
+        TriangularMatrix::<usize>::parse(file_name, &file_content)
+    }
+}
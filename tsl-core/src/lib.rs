@@ -0,0 +1,19 @@
+extern crate rand;
+extern crate time;
+
+pub mod candidates;
+pub mod checkpoint;
+pub mod config;
+pub mod coordinates;
+pub mod error;
+pub mod instance;
+pub mod lin_kernighan;
+pub mod neighbourhood;
+pub mod observer;
+pub mod random_generator;
+pub mod solver;
+pub mod stopping;
+pub mod tour;
+pub mod trace;
+pub mod triangular;
+pub mod tsplib;
@@ -0,0 +1,222 @@
+use std::*;
+
+use crate::candidates::CandidateLists;
+use crate::tour;
+use crate::triangular::TriangularMatrix;
+
+// Mejora de profundidad variable de Lin y Kernighan, con la cadena de cambios
+// hecha como una sucesion de movimientos 2-opt.
+//
+// Se trabaja sobre el recorrido completo, con el origen, girado para que la
+// ciudad base t1 quede en la posicion 0 y su sucesora t2 en la 1. Cada paso
+// quita la arista (t1, t2), anade (t2, t3) para una t3 cercana a t2, quita
+// (t4, t3) con t4 la anterior a t3 y cierra con (t1, t4) invirtiendo el tramo
+// de t2 a t4; t4 es la t2 del paso siguiente. La cadena sigue mientras la
+// ganancia parcial sea positiva y al final se vuelve al paso con el recorrido
+// mas corto, o al principio si ninguno mejora.
+pub struct LinKernighan {
+    candidates: CandidateLists,
+    max_depth: usize,
+    breadth: usize,
+    cycle: Vec<usize>,
+    position: Vec<usize>,
+    next_base: usize,
+    evaluations: usize,
+}
+
+// Cadena de cambios aplicada: cuanto acorta el recorrido y cuantos pasos tiene.
+#[derive(Clone, Copy, Debug)]
+pub struct Improvement {
+    pub gain: f64,
+    pub depth: usize,
+}
+
+// Aristas quitadas y anadidas en la cadena en curso; una arista quitada no se
+// vuelve a anadir ni una anadida se quita.
+#[derive(Default)]
+struct Chain {
+    removed: Vec<(usize, usize)>,
+    added: Vec<(usize, usize)>,
+    reversals: Vec<usize>,
+}
+
+fn edge(from: usize, to: usize) -> (usize, usize) {
+    (cmp::min(from, to), cmp::max(from, to))
+}
+
+impl LinKernighan {
+    // neighbours es cuantas ciudades cercanas se prueban como t3, max_depth el
+    // numero maximo de pasos de una cadena y breadth cuantas t3 se prueban en el
+    // primer paso antes de dar por perdida la ciudad base.
+    pub fn new(cost_map: &TriangularMatrix<usize>, neighbours: usize, max_depth: usize, breadth: usize)
+               -> LinKernighan {
+        let nodes = cost_map.number_of_lines;
+        LinKernighan {
+            candidates: CandidateLists::new(cost_map, neighbours),
+            max_depth,
+            breadth,
+            cycle: Vec::new(),
+            position: vec![0; nodes],
+            next_base: 0,
+            evaluations: 0,
+        }
+    }
+
+    pub fn set_solution(&mut self, solution: &[usize]) {
+        self.cycle = tour::to_cycle(solution);
+        self.update_positions(0, self.cycle.len() - 1);
+    }
+
+    pub fn solution(&self) -> Vec<usize> {
+        tour::from_cycle(&self.cycle)
+    }
+
+    // Ganancias parciales calculadas hasta ahora.
+    pub fn evaluations(&self) -> usize {
+        self.evaluations
+    }
+
+    // Busca desde la siguiente ciudad base una cadena que acorte el recorrido y
+    // la aplica. None si ninguna ciudad base, en ningun sentido, lo mejora: el
+    // recorrido es un optimo local. Con max_depth 0 no se permite ninguna
+    // cadena, asi que tampoco se mejora nada.
+    pub fn improve_step(&mut self, cost_map: &TriangularMatrix<usize>) -> Option<Improvement> {
+        if self.max_depth == 0 {
+            return None;
+        }
+        let nodes = self.cycle.len();
+        for _ in 0..nodes {
+            let base = self.next_base;
+            // En los dos sentidos del recorrido; el recorrido se deja siempre en
+            // el sentido que tenia para que se vea que parte ha cambiado.
+            for reversed in [false, true] {
+                self.rotate_to(base);
+                let improvement = self.improve_from_base(cost_map);
+                if reversed || improvement.is_none() {
+                    self.cycle[1..].reverse();
+                    self.update_positions(1, nodes - 1);
+                }
+                if improvement.is_some() {
+                    return improvement;
+                }
+            }
+            self.next_base = (base + 1) % nodes;
+        }
+        None
+    }
+
+    // Aplica cadenas hasta llegar a un optimo local y devuelve la ganancia total.
+    pub fn optimise(&mut self, cost_map: &TriangularMatrix<usize>) -> f64 {
+        let mut gain = 0.0;
+        while let Some(improvement) = self.improve_step(cost_map) {
+            gain += improvement.gain;
+        }
+        gain
+    }
+
+    fn update_positions(&mut self, from: usize, to: usize) {
+        for index in from..=to {
+            self.position[self.cycle[index]] = index;
+        }
+    }
+
+    fn rotate_to(&mut self, base: usize) {
+        let shift = self.position[base];
+        self.cycle.rotate_left(shift);
+        self.update_positions(0, self.cycle.len() - 1);
+    }
+
+    fn reverse(&mut self, chain: &mut Chain, last: usize) {
+        self.cycle[1..=last].reverse();
+        self.update_positions(1, last);
+        chain.reversals.push(last);
+    }
+
+    fn undo_to(&mut self, chain: &mut Chain, depth: usize) {
+        while chain.reversals.len() > depth {
+            let last = chain.reversals.pop().unwrap();
+            self.cycle[1..=last].reverse();
+            self.update_positions(1, last);
+        }
+    }
+
+    // Candidatas a t3 para la t2 actual, con su ganancia parcial, ordenadas por
+    // la ganancia que quedaria tras quitar (t4, t3).
+    fn choices(&mut self, cost_map: &TriangularMatrix<usize>, chain: &Chain, gain: f64) -> Vec<(usize, f64)> {
+        let d = |from, to| tour::distance(cost_map, from, to) as f64;
+        let t2 = self.cycle[1];
+        let mut choices = Vec::new();
+        for &t3 in self.candidates.of(t2).iter() {
+            let partial_gain = gain - d(t2, t3);
+            self.evaluations += 1;
+            if partial_gain <= 0.0 {
+                break;
+            }
+            // t3 no puede ser t1, t2 ni la siguiente a t2.
+            let t3_position = self.position[t3];
+            if t3_position < 3 {
+                continue;
+            }
+            let t4 = self.cycle[t3_position - 1];
+            if chain.removed.contains(&edge(t2, t3)) || chain.added.contains(&edge(t4, t3)) {
+                continue;
+            }
+            choices.push((t3, partial_gain));
+        }
+        let mut scored: Vec<(usize, f64, f64)> = choices.into_iter()
+            .map(|(t3, partial_gain)| {
+                let t4 = self.cycle[self.position[t3] - 1];
+                (t3, partial_gain, partial_gain + d(t4, t3))
+            })
+            .collect();
+        scored.sort_by(|first, second| second.2.partial_cmp(&first.2).unwrap());
+        scored.into_iter().map(|(t3, partial_gain, _)| (t3, partial_gain)).collect()
+    }
+
+    // Da un paso con t3: devuelve la nueva ganancia parcial (sin la arista de
+    // cierre) y lo que se acorta el recorrido tras cerrarlo.
+    fn step(&mut self, cost_map: &TriangularMatrix<usize>, chain: &mut Chain, t3: usize, partial_gain: f64)
+            -> (f64, f64) {
+        let d = |from, to| tour::distance(cost_map, from, to) as f64;
+        let (t1, t2) = (self.cycle[0], self.cycle[1]);
+        let t4_position = self.position[t3] - 1;
+        let t4 = self.cycle[t4_position];
+        chain.added.push(edge(t2, t3));
+        chain.removed.push(edge(t4, t3));
+        self.reverse(chain, t4_position);
+        let gain = partial_gain + d(t4, t3);
+        (gain, gain - d(t1, t4))
+    }
+
+    fn improve_from_base(&mut self, cost_map: &TriangularMatrix<usize>) -> Option<Improvement> {
+        let (t1, t2) = (self.cycle[0], self.cycle[1]);
+        let first_gain = tour::distance(cost_map, t1, t2) as f64;
+        let mut chain = Chain { removed: vec![edge(t1, t2)], ..Chain::default() };
+
+        let first_choices = self.choices(cost_map, &chain, first_gain);
+        for &(t3, partial_gain) in first_choices.iter().take(self.breadth) {
+            let mut best = Improvement { gain: 0.0, depth: 0 };
+            let (mut gain, closed_gain) = self.step(cost_map, &mut chain, t3, partial_gain);
+            if closed_gain > best.gain {
+                best = Improvement { gain: closed_gain, depth: 1 };
+            }
+            // Los pasos siguientes solo siguen la mejor candidata.
+            while chain.reversals.len() < self.max_depth {
+                let choice = self.choices(cost_map, &chain, gain).first().cloned();
+                let Some((t3, partial_gain)) = choice else { break };
+                let (next_gain, closed_gain) = self.step(cost_map, &mut chain, t3, partial_gain);
+                gain = next_gain;
+                if closed_gain > best.gain {
+                    best = Improvement { gain: closed_gain, depth: chain.reversals.len() };
+                }
+            }
+            self.undo_to(&mut chain, best.depth);
+            if best.depth > 0 {
+                return Some(best);
+            }
+            chain.removed.truncate(1);
+            chain.added.clear();
+        }
+        None
+    }
+}
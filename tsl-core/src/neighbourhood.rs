@@ -0,0 +1,573 @@
+use std::*;
+
+use crate::random_generator::RandomGenerator;
+use crate::tour;
+use crate::triangular::TriangularMatrix;
+
+// Movimiento sobre una solucion, con las posiciones de la solucion sin el
+// origen.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Move {
+    // Intercambia las ciudades de las posiciones i y j.
+    Swap(usize, usize),
+    // Invierte el tramo entre las posiciones i y j, ambas incluidas (2-opt).
+    Reversal(usize, usize),
+    // Saca la ciudad de la posicion i y la pone en la posicion j.
+    Insertion(usize, usize),
+    // Lleva las length ciudades que empiezan en from a la posicion to,
+    // invertidas si reversed.
+    OrOpt { from: usize, length: usize, to: usize, reversed: bool },
+    // Corta el recorrido antes de las posiciones i < j < k (k puede ser el
+    // final) y vuelve a unir los tramos B = [i, j) y C = [j, k) entre el
+    // principio A y el final D.
+    ThreeOpt { i: usize, j: usize, k: usize, reconnection: Reconnection },
+}
+
+// Las siete formas de volver a unir los tramos B y C de un 3-opt sin dejar el
+// recorrido como estaba.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Reconnection {
+    // A B' C D
+    ReverseFirst,
+    // A B C' D
+    ReverseSecond,
+    // A B' C' D
+    ReverseBoth,
+    // A C B D, el intercambio de tramos sin invertir ninguno.
+    Exchange,
+    // A C B' D
+    ExchangeReverseFirst,
+    // A C' B D
+    ExchangeReverseSecond,
+    // A C' B' D, que es invertir B y C juntos.
+    ExchangeReverseBoth,
+}
+
+impl Reconnection {
+    pub const ALL: [Reconnection; 7] = [
+        Reconnection::ReverseFirst,
+        Reconnection::ReverseSecond,
+        Reconnection::ReverseBoth,
+        Reconnection::Exchange,
+        Reconnection::ExchangeReverseFirst,
+        Reconnection::ExchangeReverseSecond,
+        Reconnection::ExchangeReverseBoth,
+    ];
+
+    // Las que cambian las tres aristas cortadas; el resto invierte un solo
+    // tramo y es un movimiento 2-opt. Con un tramo de una ciudad solo
+    // Exchange cambia las tres: las demas repiten Exchange o invierten un
+    // solo tramo.
+    pub const PURE: [Reconnection; 4] = [
+        Reconnection::Exchange,
+        Reconnection::ExchangeReverseFirst,
+        Reconnection::ExchangeReverseSecond,
+        Reconnection::ReverseBoth,
+    ];
+
+    fn exchanges(&self) -> bool {
+        matches!(*self, Reconnection::Exchange | Reconnection::ExchangeReverseFirst
+            | Reconnection::ExchangeReverseSecond | Reconnection::ExchangeReverseBoth)
+    }
+
+    fn reverses_first(&self) -> bool {
+        matches!(*self, Reconnection::ReverseFirst | Reconnection::ReverseBoth
+            | Reconnection::ExchangeReverseFirst | Reconnection::ExchangeReverseBoth)
+    }
+
+    fn reverses_second(&self) -> bool {
+        matches!(*self, Reconnection::ReverseSecond | Reconnection::ReverseBoth
+            | Reconnection::ExchangeReverseSecond | Reconnection::ExchangeReverseBoth)
+    }
+
+    // Invertir un tramo de una sola ciudad no lo cambia.
+    fn is_identity(&self, first_length: usize, second_length: usize) -> bool {
+        !self.exchanges()
+            && (!self.reverses_first() || first_length == 1)
+            && (!self.reverses_second() || second_length == 1)
+    }
+}
+
+impl fmt::Display for Reconnection {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let first = if self.reverses_first() { "B'" } else { "B" };
+        let second = if self.reverses_second() { "C'" } else { "C" };
+        if self.exchanges() {
+            write!(f, "{} {}", second, first)
+        } else {
+            write!(f, "{} {}", first, second)
+        }
+    }
+}
+
+impl Move {
+    // Variacion del coste en O(1), sin construir el vecino.
+    pub fn delta(&self, cost_map: &TriangularMatrix<usize>, solution: &[usize]) -> f64 {
+        match *self {
+            Move::Swap(i, j) => tour::swap_delta(cost_map, solution, i, j),
+            Move::Reversal(i, j) => tour::reversal_delta(cost_map, solution, i, j),
+            Move::Insertion(i, j) => tour::or_opt_delta(cost_map, solution, i, 1, j, false),
+            Move::OrOpt { from, length, to, reversed } =>
+                tour::or_opt_delta(cost_map, solution, from, length, to, reversed),
+            Move::ThreeOpt { i, j, k, reconnection } => three_opt_delta(cost_map, solution, i, j, k, reconnection),
+        }
+    }
+
+    pub fn apply(&self, solution: &mut [usize]) {
+        match *self {
+            Move::Swap(i, j) => solution.swap(i, j),
+            Move::Reversal(i, j) => solution[cmp::min(i, j)..=cmp::max(i, j)].reverse(),
+            Move::Insertion(i, j) => move_segment(solution, i, 1, j, false),
+            Move::OrOpt { from, length, to, reversed } => move_segment(solution, from, length, to, reversed),
+            Move::ThreeOpt { i, j, k, reconnection } => {
+                let (mut first, mut second) = (i..j, j..k);
+                if reconnection.exchanges() {
+                    solution[i..k].rotate_left(j - i);
+                    first = k - (j - i)..k;
+                    second = i..k - (j - i);
+                }
+                if reconnection.reverses_first() {
+                    solution[first].reverse();
+                }
+                if reconnection.reverses_second() {
+                    solution[second].reverse();
+                }
+            }
+        }
+    }
+
+    pub fn applied_to(&self, solution: &[usize]) -> Vec<usize> {
+        let mut neighbour = solution.to_vec();
+        self.apply(&mut neighbour);
+        neighbour
+    }
+
+    // Par de posiciones con el que el movimiento aparece en las trazas y en las
+    // listas tabu. En un 3-opt, los cortes de los extremos.
+    pub fn positions(&self) -> (usize, usize) {
+        match *self {
+            Move::Swap(i, j) | Move::Reversal(i, j) | Move::Insertion(i, j) => (i, j),
+            Move::OrOpt { from, to, .. } => (from, to),
+            Move::ThreeOpt { i, k, .. } => (i, k),
+        }
+    }
+
+    // Posiciones del movimiento que deshace este: lo que se lleva de i a j
+    // vuelve llevandolo de j a i. Las listas tabu prohiben los dos pares.
+    pub fn undo_positions(&self) -> (usize, usize) {
+        let (i, j) = self.positions();
+        (j, i)
+    }
+
+    // Forma compacta y sin comas, para las trazas y los puntos de control: el
+    // vecindario y los numeros que definen el movimiento, con la union de un
+    // 3-opt como su posicion en Reconnection::ALL.
+    pub fn code(&self) -> String {
+        match *self {
+            Move::Swap(i, j) => format!("swap {} {}", i, j),
+            Move::Reversal(i, j) => format!("two-opt {} {}", i, j),
+            Move::Insertion(i, j) => format!("insertion {} {}", i, j),
+            Move::OrOpt { from, length, to, reversed: false } => format!("or-opt {} {} {}", from, length, to),
+            Move::OrOpt { from, length, to, reversed: true } => format!("or-opt {} {} {} reversed", from, length, to),
+            Move::ThreeOpt { i, j, k, reconnection } => {
+                let reconnection = Reconnection::ALL.iter().position(|&other| other == reconnection).unwrap();
+                format!("three-opt {} {} {} {}", i, j, k, reconnection)
+            }
+        }
+    }
+
+    // Lee la forma de code. None si no es un movimiento valido sobre una
+    // solucion de solution_size posiciones.
+    pub fn from_code(code: &str, solution_size: usize) -> Option<Move> {
+        let mut words = code.split_whitespace();
+        let kind = words.next()?;
+        let mut numbers = Vec::new();
+        let mut reversed = false;
+        for word in words {
+            match word.parse::<usize>() {
+                Ok(number) if !reversed => numbers.push(number),
+                Err(_) if word == "reversed" && kind == "or-opt" && !reversed => reversed = true,
+                _ => return None,
+            }
+        }
+        let movement = match (kind, &numbers[..]) {
+            ("swap", &[i, j]) if i != j && i < solution_size && j < solution_size => Move::Swap(i, j),
+            ("two-opt", &[i, j]) if i != j && i < solution_size && j < solution_size => Move::Reversal(i, j),
+            ("insertion", &[i, j]) if i != j && i < solution_size && j < solution_size => Move::Insertion(i, j),
+            ("or-opt", &[from, length, to]) if length > 0 && from != to
+                && from + length <= solution_size && to + length <= solution_size =>
+                Move::OrOpt { from, length, to, reversed },
+            ("three-opt", &[i, j, k, reconnection]) if i < j && j < k && k <= solution_size
+                && reconnection < Reconnection::ALL.len() =>
+                Move::ThreeOpt { i, j, k, reconnection: Reconnection::ALL[reconnection] },
+            _ => return None,
+        };
+        Some(movement)
+    }
+}
+
+impl fmt::Display for Move {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Move::Swap(i, j) => write!(f, "Intercambio: ({}, {})", i, j),
+            Move::Reversal(i, j) => write!(f, "Inversion: ({}, {})", i, j),
+            Move::Insertion(i, j) => write!(f, "Insercion: ({}, {})", i, j),
+            Move::OrOpt { from, length, to, reversed } => {
+                write!(f, "Or-opt: ({}, {}), {} ciudades", from, to, length)?;
+                if reversed { write!(f, ", invertido")?; }
+                Ok(())
+            }
+            Move::ThreeOpt { i, j, k, reconnection } => write!(f, "3-opt: ({}, {}, {}) {}", i, j, k, reconnection),
+        }
+    }
+}
+
+// El tramo se rota hasta su sitio junto con las ciudades que salta.
+fn move_segment(solution: &mut [usize], from: usize, length: usize, to: usize, reversed: bool) {
+    if to < from {
+        solution[to..from + length].rotate_right(length);
+    } else {
+        solution[from..to + length].rotate_left(length);
+    }
+    if reversed {
+        solution[to..to + length].reverse();
+    }
+}
+
+// Se quitan las tres aristas cortadas y se ponen las que unen A, los dos tramos
+// en su nuevo orden y D.
+fn three_opt_delta(cost_map: &TriangularMatrix<usize>, solution: &[usize], i: usize, j: usize, k: usize,
+                   reconnection: Reconnection) -> f64 {
+    let d = |from, to| tour::distance(cost_map, from, to) as f64;
+    let (before, after) = (tour::previous_node(solution, i), solution.get(k).cloned().unwrap_or(0));
+    let (b_first, b_last, c_first, c_last) = (solution[i], solution[j - 1], solution[j], solution[k - 1]);
+    let first = if reconnection.reverses_first() { (b_last, b_first) } else { (b_first, b_last) };
+    let second = if reconnection.reverses_second() { (c_last, c_first) } else { (c_first, c_last) };
+    let (first, second) = if reconnection.exchanges() { (second, first) } else { (first, second) };
+    d(before, first.0) + d(first.1, second.0) + d(second.1, after)
+        - d(before, b_first) - d(b_last, c_first) - d(c_last, after)
+}
+
+// Longitud maxima del tramo que mueve Or-opt.
+pub const OR_OPT_MAX_LENGTH: usize = 3;
+
+// Conjunto de movimientos que define los vecinos de una solucion.
+//
+// Los movimientos no se guardan, porque three-opt tiene del orden de n^3: se
+// recorren por filas y move_at da el de cualquier fila y columna en O(1).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Neighbourhood {
+    Swap,
+    TwoOpt,
+    Insertion,
+    OrOpt,
+    ThreeOpt,
+}
+
+impl Neighbourhood {
+    pub const NAMES: &'static [&'static str] = &["swap", "two-opt", "insertion", "or-opt", "three-opt"];
+
+    pub fn from_name(name: &str) -> Option<Neighbourhood> {
+        match name {
+            "swap" => Some(Neighbourhood::Swap),
+            "two-opt" => Some(Neighbourhood::TwoOpt),
+            "insertion" => Some(Neighbourhood::Insertion),
+            "or-opt" => Some(Neighbourhood::OrOpt),
+            "three-opt" => Some(Neighbourhood::ThreeOpt),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Neighbourhood::Swap => "swap",
+            Neighbourhood::TwoOpt => "two-opt",
+            Neighbourhood::Insertion => "insertion",
+            Neighbourhood::OrOpt => "or-opt",
+            Neighbourhood::ThreeOpt => "three-opt",
+        }
+    }
+
+    // En los vecindarios de pares, la fila i tiene los pares (i, j) con j < i,
+    // como la matriz triangular; en insertion y or-opt, los movimientos que
+    // parten de la posicion i. En three-opt, la fila k tiene las inversiones
+    // (k, j) y los 3-opt puros con el ultimo corte en k: las uniones que
+    // invierten un solo tramo ya son inversiones y no se repiten. Primero van
+    // los cortes con tramos de al menos dos ciudades, con las cuatro uniones
+    // puras, y luego el Exchange de los que tienen un tramo de una ciudad;
+    // dos tramos de una ciudad son una inversion de dos.
+    pub fn rows(&self, solution_size: usize) -> usize {
+        match *self {
+            Neighbourhood::ThreeOpt => solution_size + 1,
+            _ => solution_size,
+        }
+    }
+
+    pub fn row_len(&self, solution_size: usize, row: usize) -> usize {
+        match *self {
+            Neighbourhood::Swap | Neighbourhood::TwoOpt => row,
+            // Llevar una ciudad a la posicion anterior es lo mismo que llevar
+            // esa otra a la siguiente, asi que se cuenta solo una vez.
+            Neighbourhood::Insertion if row == 0 => solution_size - 1,
+            Neighbourhood::Insertion => solution_size - 2,
+            Neighbourhood::OrOpt => (1..=OR_OPT_MAX_LENGTH)
+                .take_while(|length| row + length <= solution_size)
+                .map(|length| or_opt_moves(solution_size, length))
+                .sum(),
+            Neighbourhood::ThreeOpt => {
+                let reversals = if row < solution_size { row } else { 0 };
+                reversals + Reconnection::PURE.len() * long_pairs(row) + 2 * row.saturating_sub(2)
+            }
+        }
+    }
+
+    pub fn move_at(&self, solution_size: usize, row: usize, column: usize) -> Move {
+        match *self {
+            Neighbourhood::Swap => Move::Swap(row, column),
+            Neighbourhood::TwoOpt => Move::Reversal(row, column),
+            Neighbourhood::Insertion if row == 0 => Move::Insertion(row, column + 1),
+            Neighbourhood::Insertion if column + 1 < row => Move::Insertion(row, column),
+            Neighbourhood::Insertion => Move::Insertion(row, column + 2),
+            Neighbourhood::OrOpt => {
+                let mut column = column;
+                let mut length = 1;
+                while column >= or_opt_moves(solution_size, length) {
+                    column -= or_opt_moves(solution_size, length);
+                    length += 1;
+                }
+                let variants = if length == 1 { 1 } else { 2 };
+                let to = column / variants;
+                Move::OrOpt {
+                    from: row,
+                    length,
+                    to: if to < row { to } else { to + 1 },
+                    reversed: column % variants == 1,
+                }
+            }
+            Neighbourhood::ThreeOpt => {
+                let reversals = if row < solution_size { row } else { 0 };
+                if column < reversals {
+                    return Move::Reversal(row, column);
+                }
+                let column = column - reversals;
+                let long = Reconnection::PURE.len() * long_pairs(row);
+                if column < long {
+                    let (j, i) = triangular_position(column / Reconnection::PURE.len());
+                    let reconnection = Reconnection::PURE[column % Reconnection::PURE.len()];
+                    return Move::ThreeOpt { i, j: j + 1, k: row, reconnection };
+                }
+                let i = (column - long) % (row - 2);
+                // El tramo de una ciudad es el primero o el segundo.
+                let j = if column - long < row - 2 { i + 1 } else { row - 1 };
+                Move::ThreeOpt { i, j, k: row, reconnection: Reconnection::Exchange }
+            }
+        }
+    }
+
+    pub fn row(&self, solution_size: usize, row: usize) -> impl Iterator<Item = Move> {
+        let neighbourhood = *self;
+        (0..self.row_len(solution_size, row)).map(move |column| neighbourhood.move_at(solution_size, row, column))
+    }
+
+    // Todos los movimientos, siempre en el mismo orden.
+    pub fn moves(&self, solution_size: usize) -> impl Iterator<Item = Move> {
+        let neighbourhood = *self;
+        (0..self.rows(solution_size)).flat_map(move |row| neighbourhood.row(solution_size, row))
+    }
+
+    // Muestra de movimientos para los algoritmos que no recorren el vecindario
+    // entero. Se sortea una posicion y se dan los movimientos que parten de
+    // ella; en three-opt se sortean dos cortes y se dan todas las uniones con
+    // cualquier tercer corte. None si el generador se agota.
+    pub fn sample(&self, solution_size: usize, rand_gen: &mut dyn RandomGenerator) -> Option<Vec<Move>> {
+        if *self == Neighbourhood::ThreeOpt {
+            return three_opt_sample(solution_size, rand_gen);
+        }
+        let position = (rand_gen.next_random()? * (solution_size as f64)).floor() as usize;
+        let others = (0..solution_size).filter(|&i| i != position);
+        Some(match *self {
+            Neighbourhood::Swap => others.map(|i| Move::Swap(position, i)).collect(),
+            Neighbourhood::TwoOpt => others.map(|i| Move::Reversal(position, i)).collect(),
+            Neighbourhood::Insertion => others.map(|i| Move::Insertion(position, i)).collect(),
+            Neighbourhood::OrOpt | Neighbourhood::ThreeOpt => self.row(solution_size, position).collect(),
+        })
+    }
+}
+
+// Un tramo de length ciudades puede ir a cualquier otra posicion y, si tiene
+// mas de una, invertido o no.
+fn or_opt_moves(solution_size: usize, length: usize) -> usize {
+    (solution_size - length) * if length == 1 { 1 } else { 2 }
+}
+
+// Cortes i < j < k con los dos tramos de al menos dos ciudades: i < j - 1 <=
+// k - 3, que es una matriz triangular de k - 2 filas.
+fn long_pairs(k: usize) -> usize {
+    k.saturating_sub(2) * k.saturating_sub(3) / 2
+}
+
+// Fila y columna del elemento index de una matriz triangular recorrida por
+// filas, con la columna menor que la fila.
+fn triangular_position(index: usize) -> (usize, usize) {
+    let mut row = ((1.0 + (1.0 + 8.0 * index as f64).sqrt()) / 2.0) as usize;
+    while row * (row - 1) / 2 > index {
+        row -= 1;
+    }
+    while (row + 1) * row / 2 <= index {
+        row += 1;
+    }
+    (row, index - row * (row - 1) / 2)
+}
+
+fn three_opt_sample(solution_size: usize, rand_gen: &mut dyn RandomGenerator) -> Option<Vec<Move>> {
+    let cuts = solution_size + 1;
+    let first = (rand_gen.next_random()? * (cuts as f64)).floor() as usize;
+    let mut second = (rand_gen.next_random()? * (cuts as f64)).floor() as usize;
+    if second == first {
+        second = (first + 1) % cuts;
+    }
+    let mut moves = Vec::new();
+    for third in (0..cuts).filter(|&cut| cut != first && cut != second) {
+        let mut cuts = [first, second, third];
+        cuts.sort_unstable();
+        let [i, j, k] = cuts;
+        moves.extend(Reconnection::ALL.iter()
+            .filter(|reconnection| !reconnection.is_identity(j - i, k - j))
+            .map(|&reconnection| Move::ThreeOpt { i, j, k, reconnection }));
+    }
+    Some(moves)
+}
+
+impl fmt::Display for Neighbourhood {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl str::FromStr for Neighbourhood {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Neighbourhood, String> {
+        Neighbourhood::from_name(name).ok_or_else(|| format!("vecindario desconocido: {}", name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use crate::tour::tests::assert_delta;
+    use crate::tour::tests::instance;
+    use crate::tour::tests::solutions;
+    use crate::tour::tests::SIZES;
+
+    const NEIGHBOURHOODS: [Neighbourhood; 5] = [
+        Neighbourhood::Swap,
+        Neighbourhood::TwoOpt,
+        Neighbourhood::Insertion,
+        Neighbourhood::OrOpt,
+        Neighbourhood::ThreeOpt,
+    ];
+
+    #[test]
+    fn delta_of_every_move_of_every_neighbourhood() {
+        for neighbourhood in NEIGHBOURHOODS {
+            for size in SIZES {
+                let cost_map = instance(size + 1);
+                for solution in solutions(size) {
+                    for movement in neighbourhood.moves(size) {
+                        let neighbour = movement.applied_to(&solution);
+                        assert_ne!(neighbour, solution, "{}: {} no cambia la solucion", neighbourhood, movement);
+                        assert_delta(&cost_map, &solution, &neighbour, movement.delta(&cost_map, &solution),
+                                     movement);
+                    }
+                }
+            }
+        }
+    }
+
+    // Cada movimiento de una fila lleva a un vecino distinto, y en three-opt
+    // los de todo el vecindario: ni las uniones con un tramo de una ciudad
+    // repiten otra union ni una inversion.
+    #[test]
+    fn every_move_of_a_row_gives_a_different_neighbour() {
+        for neighbourhood in NEIGHBOURHOODS {
+            for size in SIZES {
+                let solution: Vec<usize> = (1..=size).collect();
+                let mut all = HashMap::new();
+                for row in 0..neighbourhood.rows(size) {
+                    let mut neighbours = HashMap::new();
+                    for movement in neighbourhood.row(size, row) {
+                        let neighbour = movement.applied_to(&solution);
+                        if let Some(other) = neighbours.insert(neighbour.clone(), movement) {
+                            panic!("{}: {} y {} dan el mismo vecino", neighbourhood, other, movement);
+                        }
+                        if neighbourhood == Neighbourhood::ThreeOpt {
+                            if let Some(other) = all.insert(neighbour, movement) {
+                                panic!("{}: {} y {} dan el mismo vecino", neighbourhood, other, movement);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    // Todas las uniones con cualquier trio de cortes, tambien antes de la
+    // primera posicion y al final del recorrido, donde los tramos A y D estan
+    // vacios y se une con el origen.
+    #[test]
+    fn delta_of_every_three_opt_reconnection() {
+        for size in SIZES {
+            let cost_map = instance(size + 1);
+            for solution in solutions(size) {
+                for k in 0..=size {
+                    for j in 0..k {
+                        for i in 0..j {
+                            for reconnection in Reconnection::ALL {
+                                let mut first = solution[i..j].to_vec();
+                                let mut second = solution[j..k].to_vec();
+                                if reconnection.reverses_first() {
+                                    first.reverse();
+                                }
+                                if reconnection.reverses_second() {
+                                    second.reverse();
+                                }
+                                if reconnection.exchanges() {
+                                    mem::swap(&mut first, &mut second);
+                                }
+                                let neighbour: Vec<usize> = solution[..i].iter()
+                                    .chain(first.iter())
+                                    .chain(second.iter())
+                                    .chain(solution[k..].iter())
+                                    .cloned()
+                                    .collect();
+                                let movement = Move::ThreeOpt { i, j, k, reconnection };
+                                assert_eq!(movement.applied_to(&solution), neighbour, "{}", movement);
+                                assert_delta(&cost_map, &solution, &neighbour, movement.delta(&cost_map, &solution),
+                                             movement);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+    // Las trazas y los puntos de control guardan los movimientos con code.
+    #[test]
+    fn code_of_every_move_reads_back() {
+        for neighbourhood in NEIGHBOURHOODS {
+            for size in SIZES {
+                for movement in neighbourhood.moves(size) {
+                    assert!(!movement.code().contains(','), "{}", movement.code());
+                    assert_eq!(Move::from_code(&movement.code(), size), Some(movement), "{}", movement.code());
+                }
+            }
+        }
+        for code in ["", "swap 1", "swap 1 1", "swap 1 9", "two-opt 1 2 3", "insertion 1 x",
+                     "or-opt 0 2 1 reversed 3", "or-opt 0 0 1", "or-opt 0 2 7", "swap 0 1 reversed",
+                     "three-opt 2 1 3 0", "three-opt 0 1 9 0", "three-opt 0 1 2 7", "span 1 2"] {
+            assert_eq!(Move::from_code(code, 8), None, "{:?}", code);
+        }
+    }
+}
@@ -0,0 +1,45 @@
+use crate::neighbourhood::Move;
+
+// Cambio que una iteracion hace en la solucion. Un movimiento del vecindario se
+// puede repetir sobre la solucion anterior; de una cadena de Lin-Kernighan o de
+// un tramo barajado solo se da el primer y el ultimo puesto que cambian.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Change {
+    Move(Move),
+    Span(usize, usize),
+}
+
+impl Change {
+    pub fn positions(&self) -> (usize, usize) {
+        match *self {
+            Change::Move(movement) => movement.positions(),
+            Change::Span(first, last) => (first, last),
+        }
+    }
+
+    // Como Move::code, con "span" para los tramos.
+    pub fn code(&self) -> String {
+        match *self {
+            Change::Move(movement) => movement.code(),
+            Change::Span(first, last) => format!("span {} {}", first, last),
+        }
+    }
+}
+
+// Eventos de la busqueda. Cada PathFinder avisa a sus observadores, que reciben
+// el propio PathFinder para consultar su estado; ninguno imprime nada por si
+// mismo.
+//
+// Un movimiento es evaluado cuando se somete al criterio de aceptacion del
+// algoritmo y aceptado cuando pasa a ser la solucion actual. on_iteration se
+// llama al final de cada iteracion, con el estado ya actualizado.
+pub trait Observer<S: ?Sized> {
+    fn on_start(&mut self, _solver: &S) {}
+    fn on_move_evaluated(&mut self, _solver: &S, _movement: Change, _cost: f64) {}
+    fn on_move_accepted(&mut self, _solver: &S, _movement: Change, _cost: f64) {}
+    fn on_restart(&mut self, _solver: &S, _restart: usize) {}
+    fn on_cooldown(&mut self, _solver: &S, _cooldown: usize, _temperature: f64) {}
+    fn on_new_best(&mut self, _solver: &S, _cost: f64) {}
+    fn on_iteration(&mut self, _solver: &S) {}
+    fn on_finish(&mut self, _solver: &S) {}
+}
@@ -0,0 +1,304 @@
+use std::*;
+use std::io::Write;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+
+use rand::Rng;
+
+use crate::error;
+use crate::error::LoadError;
+use crate::error::WriteFailure;
+
+pub trait RandomGenerator {
+    // None si es un lector estricto que se ha quedado sin aleatorios o una
+    // grabacion que no ha podido escribir; a partir de ahi no vuelve a dar
+    // ninguno.
+    fn next_random(&mut self) -> Option<f64>;
+
+    fn is_exhausted(&self) -> bool {
+        false
+    }
+
+    // Posicion en la secuencia, para los puntos de control. None si el
+    // generador no se puede reanudar.
+    fn state(&self) -> Option<String> {
+        None
+    }
+
+    fn restore_state(&mut self, _state: &str) -> Result<(), String> {
+        Err("el generador de aleatorios no se puede reanudar".to_string())
+    }
+}
+
+pub struct RustRand {
+    generator: rand::ThreadRng,
+}
+
+impl RustRand {
+    pub fn new() -> RustRand {
+        RustRand { generator: rand::thread_rng() }
+    }
+}
+
+impl Default for RustRand {
+    fn default() -> RustRand {
+        RustRand::new()
+    }
+}
+
+impl RandomGenerator for RustRand {
+    fn next_random(&mut self) -> Option<f64> {
+        Some(self.generator.next_f64())
+    }
+}
+
+// xoshiro256** inicializado con splitmix64: la misma semilla da la misma
+// secuencia en cualquier maquina.
+pub struct SeededRand {
+    state: [u64; 4],
+}
+
+impl SeededRand {
+    pub fn new(seed: u64) -> SeededRand {
+        let mut splitmix_state = seed;
+        let mut splitmix = || {
+            splitmix_state = splitmix_state.wrapping_add(0x9e3779b97f4a7c15);
+            let mut z = splitmix_state;
+            z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+            z ^ (z >> 31)
+        };
+        SeededRand { state: [splitmix(), splitmix(), splitmix(), splitmix()] }
+    }
+
+    // Semilla para las ejecuciones que no fijan ninguna.
+    pub fn random_seed() -> u64 {
+        rand::random::<u64>()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let result = self.state[1].wrapping_mul(5).rotate_left(7).wrapping_mul(9);
+        let t = self.state[1] << 17;
+
+        self.state[2] ^= self.state[0];
+        self.state[3] ^= self.state[1];
+        self.state[1] ^= self.state[2];
+        self.state[0] ^= self.state[3];
+        self.state[2] ^= t;
+        self.state[3] = self.state[3].rotate_left(45);
+
+        result
+    }
+}
+
+impl RandomGenerator for SeededRand {
+    // Los 53 bits altos dan un valor uniforme en [0, 1).
+    fn next_random(&mut self) -> Option<f64> {
+        Some((self.next_u64() >> 11) as f64 * (1.0 / (1u64 << 53) as f64))
+    }
+
+    fn state(&self) -> Option<String> {
+        Some(format!("{} {} {} {}", self.state[0], self.state[1], self.state[2], self.state[3]))
+    }
+
+    fn restore_state(&mut self, state: &str) -> Result<(), String> {
+        let words: Vec<u64> = state.split_whitespace()
+            .map(|word| word.parse::<u64>())
+            .collect::<Result<_, _>>()
+            .map_err(|_| format!("estado no valido del generador: {}", state))?;
+        if words.len() != 4 {
+            return Err(format!("estado no valido del generador: {}", state));
+        }
+        self.state.copy_from_slice(&words);
+        Ok(())
+    }
+}
+
+
+// Aviso de que un RandReader estricto se ha quedado sin aleatorios, con los
+// que se habian consumido. Lo comparten el lector y los criterios de parada,
+// que detienen la busqueda en lugar de dar la iteracion que no pudo hacerse.
+#[derive(Clone, Default)]
+pub struct Exhaustion {
+    // 0 mientras queden aleatorios: un fichero nunca esta vacio.
+    consumed: Arc<AtomicUsize>,
+}
+
+impl Exhaustion {
+    pub fn new() -> Exhaustion {
+        Exhaustion::default()
+    }
+
+    pub fn consumed(&self) -> Option<usize> {
+        match self.consumed.load(Ordering::SeqCst) {
+            0 => None,
+            consumed => Some(consumed),
+        }
+    }
+}
+
+// Con exhaustion, el lector es estricto: agotar el fichero es un error en
+// lugar de volver al principio.
+pub struct RandReader {
+    file_name: String,
+    rand_list: Vec<f64>,
+    index: usize,
+    consumed: usize,
+    exhaustion: Option<Exhaustion>,
+}
+
+impl RandReader {
+    pub fn new(file_name: &str) -> Result<RandReader, LoadError> {
+        let file_content = error::read_file(file_name)?;
+
+        let mut rand_list = Vec::new();
+        for (number, line) in file_content.lines().enumerate() {
+            for (column, token) in error::tokens(line) {
+                rand_list.push(error::parse_token::<f64>(file_name, number + 1, column, token)?);
+            }
+        }
+        if rand_list.is_empty() {
+            return Err(LoadError::Empty { file_name: file_name.to_string() });
+        }
+
+        Ok(RandReader { file_name: file_name.to_string(), rand_list, index: 0, consumed: 0, exhaustion: None })
+    }
+
+    pub fn new_strict(file_name: &str, exhaustion: Exhaustion) -> Result<RandReader, LoadError> {
+        let mut rand_reader = RandReader::new(file_name)?;
+        rand_reader.exhaustion = Some(exhaustion);
+        Ok(rand_reader)
+    }
+}
+
+impl RandomGenerator for RandReader {
+    // Un lector estricto no vuelve al principio del fichero: al agotarse avisa
+    // y deja de dar aleatorios, aunque despues se restaure un estado anterior.
+    fn next_random(&mut self) -> Option<f64> {
+        if let Some(ref exhaustion) = self.exhaustion {
+            if exhaustion.consumed().is_some() {
+                return None;
+            }
+            if self.consumed == self.rand_list.len() {
+                exhaustion.consumed.store(self.consumed, Ordering::SeqCst);
+                return None;
+            }
+        }
+        let next_float = self.rand_list[self.index];
+        self.index = (self.index + 1) % self.rand_list.len();
+        self.consumed += 1;
+
+        Some(next_float)
+    }
+
+    fn is_exhausted(&self) -> bool {
+        self.exhaustion.as_ref().is_some_and(|exhaustion| exhaustion.consumed().is_some())
+    }
+
+    // Basta con los consumidos: la posicion en el fichero se deduce de ellos.
+    fn state(&self) -> Option<String> {
+        Some(self.consumed.to_string())
+    }
+
+    fn restore_state(&mut self, state: &str) -> Result<(), String> {
+        let consumed = state.parse::<usize>()
+            .map_err(|_| format!("estado no valido del generador: {}", state))?;
+        if self.exhaustion.is_some() && consumed > self.rand_list.len() {
+            return Err(format!("{}: solo tiene {} aleatorios y se habian consumido {}",
+                               self.file_name, self.rand_list.len(), consumed));
+        }
+        self.consumed = consumed;
+        self.index = consumed % self.rand_list.len();
+        Ok(())
+    }
+}
+
+
+// Saca todos los aleatorios que necesita draw o ninguno: si el generador se
+// agota a medias vuelve a su estado anterior, para que un punto de control no
+// cuente los aleatorios de una iteracion que no se ha hecho.
+pub fn draw_all<T>(rand_gen: &mut dyn RandomGenerator,
+                   draw: impl FnOnce(&mut dyn RandomGenerator) -> Option<T>) -> Option<T> {
+    let state = rand_gen.state();
+    let drawn = draw(rand_gen);
+    if drawn.is_none() {
+        if let Some(state) = state {
+            // El estado lo acaba de dar el propio generador.
+            let _ = rand_gen.restore_state(&state);
+        }
+    }
+    drawn
+}
+
+
+// Guarda cada aleatorio consumido, uno por linea, con el formato de los
+// ficheros de aleatorios para poder reproducir la ejecucion con RandReader.
+pub struct RecordingRand {
+    inner: Box<dyn RandomGenerator>,
+    file_name: String,
+    // Compartido con quien tenga que volcarlo si el programa sale sin
+    // terminar la busqueda.
+    writer: Arc<Mutex<io::BufWriter<fs::File>>>,
+    // Si no se puede escribir, el error queda en failure y no se dan mas
+    // aleatorios: la grabacion tiene que ser la de la busqueda.
+    failure: WriteFailure,
+}
+
+impl RecordingRand {
+    // Con append se sigue grabando al final del fichero, al reanudar una
+    // busqueda que ya lo habia empezado.
+    pub fn new(inner: Box<dyn RandomGenerator>, file_name: &str, append: bool, failure: WriteFailure)
+               -> io::Result<RecordingRand> {
+        let file = fs::OpenOptions::new().write(true).create(true).append(append).truncate(!append)
+            .open(file_name)?;
+        let writer = Arc::new(Mutex::new(io::BufWriter::new(file)));
+        Ok(RecordingRand { inner, file_name: file_name.to_string(), writer, failure })
+    }
+
+    pub fn writer(&self) -> Arc<Mutex<io::BufWriter<fs::File>>> {
+        self.writer.clone()
+    }
+}
+
+// El fichero se comparte, asi que no basta con que se cierre solo. Tras un
+// error ya avisado no se insiste.
+impl Drop for RecordingRand {
+    fn drop(&mut self) {
+        if self.failure.error().is_some() {
+            return;
+        }
+        let mut writer = self.writer.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        if let Err(error) = writer.flush() {
+            eprintln!("{}: imposible escribir el fichero: {}", self.file_name, error);
+        }
+    }
+}
+
+impl RandomGenerator for RecordingRand {
+    fn next_random(&mut self) -> Option<f64> {
+        if self.failure.error().is_some() {
+            return None;
+        }
+        let next_float = self.inner.next_random()?;
+        let mut writer = self.writer.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        if let Err(error) = writeln!(writer, "{}", next_float) {
+            self.failure.set(&self.file_name, error);
+            return None;
+        }
+        Some(next_float)
+    }
+
+    fn is_exhausted(&self) -> bool {
+        self.failure.error().is_some() || self.inner.is_exhausted()
+    }
+
+    fn state(&self) -> Option<String> {
+        self.inner.state()
+    }
+
+    fn restore_state(&mut self, state: &str) -> Result<(), String> {
+        self.inner.restore_state(state)
+    }
+}
@@ -0,0 +1,67 @@
+use std::*;
+
+use crate::stopping::Criterion;
+use crate::stopping::StoppingCriteria;
+
+// Interfaz comun de los algoritmos para poder manejarlos indistintamente.
+// initialise y finish avisan a los observadores del principio y del final de la
+// busqueda; step hace una iteracion.
+pub trait Solver {
+    fn initialise(&mut self);
+    fn step(&mut self);
+    fn is_finished(&self) -> bool;
+    fn finish(&mut self);
+    fn iteration(&self) -> usize;
+    // Veces que se ha calculado el coste de un recorrido.
+    fn evaluations(&self) -> usize;
+    fn current_tour(&self) -> &[usize];
+    fn current_cost(&self) -> f64;
+    fn best_tour(&self) -> &[usize];
+    fn best_cost(&self) -> f64;
+    fn summary(&self) -> String;
+
+    fn temperature(&self) -> Option<f64> {
+        None
+    }
+}
+
+// Motivos por los que un algoritmo no puede empezar la busqueda.
+#[derive(Debug)]
+pub enum SolverError {
+    // El generador se agoto antes de tener la solucion inicial.
+    RandomExhausted,
+    // La instancia tiene menos ciudades, contando el origen, de las que
+    // necesita el algoritmo.
+    TooFewCities { needed: usize, found: usize },
+}
+
+impl fmt::Display for SolverError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            SolverError::RandomExhausted =>
+                write!(f, "se han agotado los aleatorios antes de generar la solucion inicial"),
+            SolverError::TooFewCities { needed, found } =>
+                write!(f, "el algoritmo necesita al menos {} ciudades y la instancia tiene {}", needed, found),
+        }
+    }
+}
+
+impl error::Error for SolverError {}
+
+// Itera hasta que el algoritmo termina por si mismo o se cumple un criterio de
+// parada, que se devuelve. Los criterios se miran antes que el final del
+// algoritmo porque un generador agotado tambien lo termina.
+pub fn run(solver: &mut dyn Solver, criteria: &mut StoppingCriteria) -> Option<Criterion> {
+    solver.initialise();
+    criteria.start(solver);
+    let mut reached;
+    loop {
+        reached = criteria.reached(solver);
+        if reached.is_some() || solver.is_finished() {
+            break;
+        }
+        solver.step();
+    }
+    solver.finish();
+    reached
+}
@@ -0,0 +1,160 @@
+use std::*;
+use std::sync::Arc;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+
+use crate::error::WriteFailure;
+use crate::random_generator::Exhaustion;
+use crate::solver::Solver;
+
+// Condiciones de parada; la busqueda se detiene con la primera que se cumple.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Criterion {
+    Iterations(usize),
+    Seconds(f64),
+    Evaluations(usize),
+    TargetCost(f64),
+    // Iteraciones seguidas sin mejorar la mejor solucion.
+    Stagnation(usize),
+    // Solo para los algoritmos con temperatura.
+    Temperature(f64),
+    // Se ha pedido parar desde fuera, por ejemplo con una senal.
+    Interrupted,
+    // El fichero de aleatorios estricto se ha agotado tras consumir n.
+    RandomExhausted(usize),
+    // No se ha podido escribir un punto de control o la grabacion de los
+    // aleatorios.
+    WriteFailed,
+}
+
+impl fmt::Display for Criterion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Criterion::Iterations(n) => write!(f, "{} iteraciones", n),
+            Criterion::Seconds(seconds) => write!(f, "{} segundos", seconds),
+            Criterion::Evaluations(n) => write!(f, "{} evaluaciones", n),
+            Criterion::TargetCost(cost) => write!(f, "coste objetivo {}", cost),
+            Criterion::Stagnation(n) => write!(f, "{} iteraciones sin mejora", n),
+            Criterion::Temperature(temperature) => write!(f, "temperatura minima {}", temperature),
+            Criterion::Interrupted => write!(f, "interrupcion"),
+            Criterion::RandomExhausted(n) => write!(f, "aleatorios agotados tras consumir {}", n),
+            Criterion::WriteFailed => write!(f, "error de escritura"),
+        }
+    }
+}
+
+// Iteracion en la que mejoro por ultima vez la mejor solucion, de la que
+// cuenta el estancamiento. Los puntos de control la guardan para que una
+// busqueda reanudada siga contando desde ella y no desde la reanudacion.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct LastImprovement {
+    best_cost: f64,
+    iteration: usize,
+}
+
+impl LastImprovement {
+    // iteration es la guardada, si se reanuda la busqueda.
+    pub fn new(solver: &dyn Solver, iteration: Option<usize>) -> LastImprovement {
+        LastImprovement {
+            best_cost: solver.best_cost(),
+            iteration: iteration.unwrap_or(solver.iteration()),
+        }
+    }
+
+    pub fn update(&mut self, solver: &dyn Solver) {
+        if solver.best_cost() < self.best_cost {
+            self.best_cost = solver.best_cost();
+            self.iteration = solver.iteration();
+        }
+    }
+
+    pub fn iteration(&self) -> usize {
+        self.iteration
+    }
+}
+
+#[derive(Default)]
+pub struct StoppingCriteria {
+    criteria: Vec<Criterion>,
+    start_time: f64,
+    last_improvement: LastImprovement,
+    resumed_improvement: Option<usize>,
+    interruption: Option<Arc<AtomicBool>>,
+    exhaustion: Option<Exhaustion>,
+    write_failure: Option<WriteFailure>,
+}
+
+impl StoppingCriteria {
+    pub fn new() -> StoppingCriteria {
+        StoppingCriteria::default()
+    }
+
+    pub fn with(mut self, criterion: Criterion) -> StoppingCriteria {
+        self.criteria.push(criterion);
+        self
+    }
+
+    // La busqueda se interrumpe en cuanto flag se pone a true; la iteracion en
+    // curso siempre se completa.
+    pub fn with_interruption(mut self, flag: Arc<AtomicBool>) -> StoppingCriteria {
+        self.interruption = Some(flag);
+        self
+    }
+
+    // Al reanudar, la iteracion de la ultima mejora guardada en el punto de
+    // control.
+    pub fn with_last_improvement(mut self, iteration: usize) -> StoppingCriteria {
+        self.resumed_improvement = Some(iteration);
+        self
+    }
+
+    pub fn with_random_exhaustion(mut self, exhaustion: Exhaustion) -> StoppingCriteria {
+        self.exhaustion = Some(exhaustion);
+        self
+    }
+
+    pub fn with_write_failure(mut self, failure: WriteFailure) -> StoppingCriteria {
+        self.write_failure = Some(failure);
+        self
+    }
+
+    // La interrupcion y el agotamiento de los aleatorios no cuentan: por si
+    // solos no terminan la busqueda.
+    pub fn is_empty(&self) -> bool {
+        self.criteria.is_empty()
+    }
+
+    pub fn start(&mut self, solver: &dyn Solver) {
+        self.start_time = ::time::precise_time_s();
+        self.last_improvement = LastImprovement::new(solver, self.resumed_improvement);
+    }
+
+    // Se consulta antes de cada iteracion.
+    pub fn reached(&mut self, solver: &dyn Solver) -> Option<Criterion> {
+        self.last_improvement.update(solver);
+
+        if self.interruption.as_ref().is_some_and(|flag| flag.load(Ordering::SeqCst)) {
+            return Some(Criterion::Interrupted);
+        }
+        if let Some(consumed) = self.exhaustion.as_ref().and_then(Exhaustion::consumed) {
+            return Some(Criterion::RandomExhausted(consumed));
+        }
+        if self.write_failure.as_ref().is_some_and(|failure| failure.error().is_some()) {
+            return Some(Criterion::WriteFailed);
+        }
+
+        let elapsed = ::time::precise_time_s() - self.start_time;
+        let stagnation = solver.iteration() - self.last_improvement.iteration();
+        self.criteria.iter()
+            .find(|criterion| match **criterion {
+                Criterion::Iterations(n) => solver.iteration() >= n,
+                Criterion::Seconds(seconds) => elapsed >= seconds,
+                Criterion::Evaluations(n) => solver.evaluations() >= n,
+                Criterion::TargetCost(cost) => solver.best_cost() <= cost,
+                Criterion::Stagnation(n) => stagnation >= n,
+                Criterion::Temperature(floor) => solver.temperature().is_some_and(|t| t <= floor),
+                Criterion::Interrupted | Criterion::RandomExhausted(_) | Criterion::WriteFailed => false,
+            })
+            .cloned()
+    }
+}
@@ -0,0 +1,242 @@
+use std::*;
+
+use crate::random_generator::RandomGenerator;
+use crate::triangular::TriangularMatrix;
+
+// Las soluciones no incluyen el nodo 0: el recorrido sale y vuelve a él.
+pub fn calculate_cost(cost_map: &TriangularMatrix<usize>, solution: &[usize]) -> f64 {
+    let solution_size = solution.len();
+    let mut total_cost: usize = 0;
+
+    total_cost += *cost_map.get(solution[0], 0);
+
+    for i in 0..(solution_size - 1) {
+        let node_to = cmp::max(solution[i], solution[i + 1]);
+        let node_from = cmp::min(solution[i], solution[i + 1]);
+        total_cost += *cost_map.get(node_to, node_from);
+    }
+
+    total_cost += *cost_map.get(solution[solution_size - 1], 0);
+
+    total_cost as f64
+}
+
+// Recorrido completo que empieza en el origen.
+pub fn to_cycle(solution: &[usize]) -> Vec<usize> {
+    iter::once(0).chain(solution.iter().cloned()).collect()
+}
+
+// Solucion a partir de un recorrido completo, girado para que el origen quede
+// al principio.
+pub fn from_cycle(cycle: &[usize]) -> Vec<usize> {
+    let depot = cycle.iter().position(|&node| node == 0).unwrap_or(0);
+    cycle[depot + 1..].iter()
+        .chain(cycle[..depot].iter())
+        .cloned()
+        .collect()
+}
+
+// None si el generador se agota antes de completarla.
+pub fn generate_rand_solution(rand_gen: &mut dyn RandomGenerator, solution_size: usize) -> Option<Vec<usize>> {
+    let mut rand_solution: Vec<usize> = Vec::with_capacity(solution_size);
+    for _ in 0..solution_size {
+        let rand_num = rand_gen.next_random()?;
+        let rand_multiplier = solution_size as f64;
+        let mut rand_position = (rand_num * rand_multiplier).floor() as usize + 1;
+        while rand_solution.contains(&rand_position) {
+            rand_position = cmp::max((rand_position + 1) % (solution_size + 1), 1);
+        }
+        rand_solution.push(rand_position);
+    }
+    Some(rand_solution)
+}
+
+// Distancia entre dos nodos cualesquiera, en cualquier orden.
+pub fn distance(cost_map: &TriangularMatrix<usize>, from: usize, to: usize) -> usize {
+    match from.cmp(&to) {
+        cmp::Ordering::Equal => 0,
+        cmp::Ordering::Greater => *cost_map.get(from, to),
+        cmp::Ordering::Less => *cost_map.get(to, from),
+    }
+}
+
+// Nodos anterior y siguiente a la posicion i; antes del primero y despues del
+// ultimo esta el origen.
+pub(crate) fn previous_node(solution: &[usize], i: usize) -> usize {
+    if i == 0 { 0 } else { solution[i - 1] }
+}
+
+pub(crate) fn next_node(solution: &[usize], i: usize) -> usize {
+    if i + 1 == solution.len() { 0 } else { solution[i + 1] }
+}
+
+// Las variaciones del coste se calculan en O(1) con las aristas que cambian,
+// sin construir el recorrido vecino. Como las distancias son enteras, el coste
+// actual mas la variacion es exactamente el coste del vecino.
+
+// Variacion del coste al intercambiar las ciudades de las posiciones i y j.
+pub fn swap_delta(cost_map: &TriangularMatrix<usize>, solution: &[usize], i: usize, j: usize) -> f64 {
+    let (i, j) = (cmp::min(i, j), cmp::max(i, j));
+    if i == j {
+        return 0.0;
+    }
+    let d = |from, to| distance(cost_map, from, to) as f64;
+    let (a, b) = (solution[i], solution[j]);
+    let (before_a, after_b) = (previous_node(solution, i), next_node(solution, j));
+    if j == i + 1 {
+        d(before_a, b) + d(a, after_b) - d(before_a, a) - d(b, after_b)
+    } else {
+        let (after_a, before_b) = (next_node(solution, i), previous_node(solution, j));
+        d(before_a, b) + d(b, after_a) + d(before_b, a) + d(a, after_b)
+            - d(before_a, a) - d(a, after_a) - d(before_b, b) - d(b, after_b)
+    }
+}
+
+// Variacion del coste al invertir el tramo entre las posiciones i y j, ambas
+// incluidas: solo cambian las dos aristas de los extremos.
+pub fn reversal_delta(cost_map: &TriangularMatrix<usize>, solution: &[usize], i: usize, j: usize) -> f64 {
+    let (i, j) = (cmp::min(i, j), cmp::max(i, j));
+    let d = |from, to| distance(cost_map, from, to) as f64;
+    let (a, b) = (solution[i], solution[j]);
+    let (before_a, after_b) = (previous_node(solution, i), next_node(solution, j));
+    d(before_a, b) + d(a, after_b) - d(before_a, a) - d(b, after_b)
+}
+
+// Variacion del coste al llevar las length ciudades que empiezan en from a la
+// posicion to del recorrido resultante, invertidas si reversed (Or-opt; con
+// length 1 es la insercion de una ciudad). Se quitan las aristas de los extremos
+// del tramo y la arista en la que se inserta, y se une el hueco.
+pub fn or_opt_delta(cost_map: &TriangularMatrix<usize>, solution: &[usize], from: usize, length: usize,
+                    to: usize, reversed: bool) -> f64 {
+    // En su sitio solo cambia si se invierte, y entonces es una inversion.
+    if from == to {
+        return if reversed { reversal_delta(cost_map, solution, from, from + length - 1) } else { 0.0 };
+    }
+    let d = |from, to| distance(cost_map, from, to) as f64;
+    let last = from + length - 1;
+    let (a, b) = (solution[from], solution[last]);
+    let (before_a, after_b) = (previous_node(solution, from), next_node(solution, last));
+    // Ciudades entre las que queda el tramo, con sus posiciones antes de quitarlo.
+    let (before, after) = if to < from {
+        (previous_node(solution, to), solution[to])
+    } else {
+        (solution[to + length - 1], next_node(solution, to + length - 1))
+    };
+    let (first, last) = if reversed { (b, a) } else { (a, b) };
+    d(before_a, after_b) + d(before, first) + d(last, after)
+        - d(before_a, a) - d(b, after_b) - d(before, after)
+}
+
+pub fn reverse_segment(solution: &[usize], i: usize, j: usize) -> Vec<usize> {
+    let mut reversed = solution.to_vec();
+    reversed[cmp::min(i, j)..(cmp::max(i, j) + 1)].reverse();
+    reversed
+}
+
+pub fn format_solution(solution: &[usize]) -> String {
+    solution.iter()
+        .fold(String::new(), |acc, e| {
+            acc + &e.to_string() + " "
+        })
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::neighbourhood::Move;
+    use crate::neighbourhood::OR_OPT_MAX_LENGTH;
+
+    // Tamanos pequenos para probar todas las posiciones, tambien las de los
+    // extremos, que son vecinas del origen.
+    pub(crate) const SIZES: ops::RangeInclusive<usize> = 2..=8;
+
+    // Distancias pseudoaleatorias entre 1 y 1000, para que una arista mal
+    // contada cambie el coste.
+    pub(crate) fn instance(nodes: usize) -> TriangularMatrix<usize> {
+        let mut cost_map = TriangularMatrix::<usize>::filled_zeros(nodes);
+        let mut state = 12345 + nodes as u64;
+        for i in 1..nodes {
+            for j in 0..i {
+                state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+                cost_map.set(i, j, (state >> 33) as usize % 1000 + 1);
+            }
+        }
+        cost_map
+    }
+
+    // Las ciudades en orden, en el orden inverso y primero las impares.
+    pub(crate) fn solutions(size: usize) -> Vec<Vec<usize>> {
+        let natural: Vec<usize> = (1..=size).collect();
+        let reversed = natural.iter().rev().cloned().collect();
+        let alternate = natural.iter().step_by(2).chain(natural.iter().skip(1).step_by(2)).cloned().collect();
+        vec![natural, reversed, alternate]
+    }
+
+    pub(crate) fn assert_delta(cost_map: &TriangularMatrix<usize>, solution: &[usize], neighbour: &[usize],
+                               delta: f64, movement: impl fmt::Display) {
+        let expected = calculate_cost(cost_map, neighbour) - calculate_cost(cost_map, solution);
+        assert_eq!(delta, expected, "{} sobre {:?}", movement, solution);
+    }
+
+    #[test]
+    fn swap_delta_of_every_pair() {
+        for size in SIZES {
+            let cost_map = instance(size + 1);
+            for solution in solutions(size) {
+                for i in 0..size {
+                    for j in 0..size {
+                        let mut neighbour = solution.clone();
+                        neighbour.swap(i, j);
+                        assert_delta(&cost_map, &solution, &neighbour, swap_delta(&cost_map, &solution, i, j),
+                                     format!("intercambio ({}, {})", i, j));
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn reversal_delta_of_every_segment() {
+        for size in SIZES {
+            let cost_map = instance(size + 1);
+            for solution in solutions(size) {
+                for i in 0..size {
+                    for j in 0..size {
+                        let neighbour = reverse_segment(&solution, i, j);
+                        assert_delta(&cost_map, &solution, &neighbour, reversal_delta(&cost_map, &solution, i, j),
+                                     format!("inversion ({}, {})", i, j));
+                    }
+                }
+            }
+        }
+    }
+
+    // El vecino se construye sacando el tramo y metiendolo en su nueva posicion.
+    #[test]
+    fn or_opt_delta_of_every_segment_and_position() {
+        for size in SIZES {
+            let cost_map = instance(size + 1);
+            for solution in solutions(size) {
+                for length in (1..=OR_OPT_MAX_LENGTH).filter(|&length| length <= size) {
+                    for from in 0..=size - length {
+                        for to in 0..=size - length {
+                            for reversed in [false, true] {
+                                let mut neighbour = solution.clone();
+                                let mut segment: Vec<usize> = neighbour.drain(from..from + length).collect();
+                                if reversed {
+                                    segment.reverse();
+                                }
+                                neighbour.splice(to..to, segment);
+                                let movement = Move::OrOpt { from, length, to, reversed };
+                                assert_eq!(movement.applied_to(&solution), neighbour, "{}", movement);
+                                assert_delta(&cost_map, &solution, &neighbour,
+                                             or_opt_delta(&cost_map, &solution, from, length, to, reversed),
+                                             movement);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,98 @@
+use std::*;
+
+use crate::observer::Change;
+use crate::observer::Observer;
+
+// Estado tras una iteracion, para las trazas en JSON Lines y CSV. Los campos
+// que no tienen sentido en un algoritmo quedan a None. movement va entero en
+// su forma de Change::code, y ademas como el par de posiciones de siempre.
+pub struct TraceRecord {
+    pub iteration: usize,
+    pub movement: Option<Change>,
+    pub current_cost: f64,
+    pub best_cost: f64,
+    pub temperature: Option<f64>,
+    pub taboo_list_size: Option<usize>,
+    pub accepted: bool,
+    pub restart: bool,
+}
+
+pub const CSV_HEADER: &str =
+    "iteration,move_i,move_j,movement,current_cost,best_cost,temperature,taboo_list_size,accepted,restart";
+
+fn or_empty<T: fmt::Display>(value: Option<T>) -> String {
+    value.map_or(String::new(), |value| value.to_string())
+}
+
+fn or_null<T: fmt::Display>(value: Option<T>) -> String {
+    value.map_or("null".to_string(), |value| value.to_string())
+}
+
+impl TraceRecord {
+    pub fn to_csv(&self) -> String {
+        format!("{},{},{},{},{},{},{},{},{},{}",
+                self.iteration,
+                or_empty(self.movement.map(|movement| movement.positions().0)),
+                or_empty(self.movement.map(|movement| movement.positions().1)),
+                or_empty(self.movement.map(|movement| movement.code())),
+                self.current_cost,
+                self.best_cost,
+                or_empty(self.temperature),
+                or_empty(self.taboo_list_size),
+                self.accepted,
+                self.restart)
+    }
+
+    pub fn to_json(&self) -> String {
+        format!("{{\"iteration\":{},\"move\":{},\"movement\":{},\"current_cost\":{},\"best_cost\":{},\
+                 \"temperature\":{},\"taboo_list_size\":{},\"accepted\":{},\"restart\":{}}}",
+                self.iteration,
+                or_null(self.movement.map(|movement| format!("[{},{}]", movement.positions().0, movement.positions().1))),
+                or_null(self.movement.map(|movement| format!("\"{}\"", movement.code()))),
+                self.current_cost,
+                self.best_cost,
+                or_null(self.temperature),
+                or_null(self.taboo_list_size),
+                self.accepted,
+                self.restart)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum RecordFormat {
+    Jsonl,
+    Csv,
+}
+
+// Observador que imprime un registro con el estado inicial y otro por iteracion.
+pub struct RecordTrace<S> {
+    trace_record: fn(&S) -> TraceRecord,
+    format: RecordFormat,
+}
+
+impl<S> RecordTrace<S> {
+    pub fn new(trace_record: fn(&S) -> TraceRecord, format: RecordFormat) -> RecordTrace<S> {
+        RecordTrace { trace_record, format }
+    }
+
+    fn print_record(&self, solver: &S) {
+        let record = (self.trace_record)(solver);
+        match self.format {
+            RecordFormat::Jsonl => println!("{}", record.to_json()),
+            RecordFormat::Csv => println!("{}", record.to_csv()),
+        }
+    }
+}
+
+impl<S> Observer<S> for RecordTrace<S> {
+    fn on_start(&mut self, solver: &S) {
+        if self.format == RecordFormat::Csv {
+            println!("{}", CSV_HEADER);
+        }
+        self.print_record(solver);
+    }
+
+    fn on_iteration(&mut self, solver: &S) {
+        self.print_record(solver);
+    }
+}
@@ -1,50 +1,67 @@
 use std::*;
-use std::fs::File;
-use std::io::prelude::*;
+
+use crate::error;
+use crate::error::LoadError;
 
 pub struct TriangularMatrix<T> {
     data: Vec<T>,
     pub number_of_lines: usize,
 }
 
-#[allow(dead_code)]
 impl<T> TriangularMatrix<T> {
-    pub fn from_file(file_name: &str) -> TriangularMatrix<usize> {
-        let mut file = File::open(file_name).expect("Imposible Abrir el fichero de distancias");
-        let file_content = &mut String::new();
-        file.read_to_string(file_content).expect(
-            "Formato del fichero de distancias incorrecto",
-        );
-
-        return TriangularMatrix {
-            data: file_content
-                .trim()
-                .split_whitespace()
-                .map(|e| {
-                    e.parse::<usize>().expect(
-                        "Elmento del fichero de distancias no es entero",
-                    )
-                })
-                .collect(),
-            number_of_lines: file_content.trim().lines().count() + 1,
-        };
+    // La linea k del fichero tiene las k distancias del nodo k a los nodos 0..k.
+    pub fn from_file(file_name: &str) -> Result<TriangularMatrix<usize>, LoadError> {
+        let file_content = error::read_file(file_name)?;
+        TriangularMatrix::<usize>::parse(file_name, &file_content)
     }
+
+    pub fn parse(file_name: &str, file_content: &str) -> Result<TriangularMatrix<usize>, LoadError> {
+        let first_line = file_content.lines()
+            .position(|line| !line.trim().is_empty())
+            .ok_or_else(|| LoadError::Empty { file_name: file_name.to_string() })?;
+
+        let mut data = Vec::new();
+        let mut number_of_lines = 1;
+        for (number, line) in file_content.trim_end().lines().enumerate().skip(first_line) {
+            let row_start = data.len();
+            for (column, token) in error::tokens(line) {
+                data.push(error::parse_token::<usize>(file_name, number + 1, column, token)?);
+            }
+            if data.len() - row_start != number_of_lines {
+                return Err(LoadError::RowLength {
+                    file_name: file_name.to_string(),
+                    line: number + 1,
+                    expected: number_of_lines,
+                    found: data.len() - row_start,
+                });
+            }
+            number_of_lines += 1;
+        }
+
+        Ok(TriangularMatrix { data, number_of_lines })
+    }
+
     pub fn filled_false(number_of_lines: usize) -> TriangularMatrix<bool> {
         let capacity = (number_of_lines * number_of_lines - number_of_lines) / 2;
-        let mut data = Vec::with_capacity(capacity);
-        for _ in 0..capacity {
-            data.push(false);
+        TriangularMatrix {
+            data: vec![false; capacity],
+            number_of_lines,
         }
+    }
+
+    pub fn filled_zeros(number_of_lines: usize) -> TriangularMatrix<usize> {
+        let capacity = (number_of_lines * number_of_lines - number_of_lines) / 2;
         TriangularMatrix {
-            data,
+            data: vec![0; capacity],
             number_of_lines,
         }
     }
+
     fn check_index(&self, line: usize, column: usize) {
         if column >= line {
             panic!("Impossible to access {},{} element", line, column)
         }
-        if line > self.number_of_lines {
+        if line >= self.number_of_lines {
             panic!(
                 "Impossible to access {},{} element with {} columns",
                 line,
@@ -54,18 +71,6 @@ impl<T> TriangularMatrix<T> {
         }
     }
 
-    pub fn filled_zeros(number_of_lines: usize) -> TriangularMatrix<usize> {
-        let capacity = (number_of_lines * number_of_lines - number_of_lines) / 2;
-        let mut data = Vec::with_capacity(capacity);
-        for _ in 0..capacity {
-            data.push(0);
-        }
-        TriangularMatrix {
-            data,
-            number_of_lines,
-        }
-    }
-
     pub fn get(&self, line: usize, column: usize) -> &T {
         self.check_index(line, column);
         let line_jump = (line * line - line) / 2; //N * (N -1)  / 2
@@ -78,20 +83,20 @@ impl<T> TriangularMatrix<T> {
         self.data[line_jump + column] = value
     }
 
-    pub fn enumerate_indexes(&self) -> TriangularMultiIndexEnumerate<T> {
-        return TriangularMultiIndexEnumerate {
+    pub fn enumerate_indexes(&self) -> TriangularMultiIndexEnumerate<'_, T> {
+        TriangularMultiIndexEnumerate {
             index: (1, 0),
             number_of_lines: self.number_of_lines,
             matrix: &self.data,
-        };
+        }
     }
 
-    pub fn enumerate_from(&self, column: usize, line: usize) -> TriangularMultiIndexEnumerate<T> {
-        return TriangularMultiIndexEnumerate {
+    pub fn enumerate_from(&self, column: usize, line: usize) -> TriangularMultiIndexEnumerate<'_, T> {
+        TriangularMultiIndexEnumerate {
             index: (column, line),
             number_of_lines: self.number_of_lines,
             matrix: &self.data,
-        };
+        }
     }
 }
 
@@ -108,7 +113,7 @@ impl<T: cmp::Ord> TriangularMatrix<T> {
 pub struct TriangularMultiIndexEnumerate<'a, T: 'a> {
     index: (usize, usize),
     number_of_lines: usize,
-    matrix: &'a Vec<T>,
+    matrix: &'a [T],
 }
 
 impl<'a, T: 'a> Iterator for TriangularMultiIndexEnumerate<'a, T> {
@@ -121,11 +126,11 @@ impl<'a, T: 'a> Iterator for TriangularMultiIndexEnumerate<'a, T> {
         self.index = (line + next_column / line, next_column % line);
 
         if line >= self.number_of_lines {
-            return None;
+            None
         } else {
             let line_jump = (line * line - line) / 2;
             let value = &self.matrix[column + line_jump];
-            return Some((line, column, value));
+            Some((line, column, value))
         }
     }
 }
@@ -137,15 +142,13 @@ pub struct FreqMatrix {
 
 impl FreqMatrix {
     pub fn new(size: usize) -> FreqMatrix {
-        let new_freq = FreqMatrix {
+        FreqMatrix {
             frec_data: TriangularMatrix::<usize>::filled_zeros(size),
             max_frec: 1,
-        };
-
-        new_freq
+        }
     }
 
-    pub fn insert_solution(&mut self, solution: &Vec<usize>) {
+    pub fn insert_solution(&mut self, solution: &[usize]) {
         for i in 1..solution.len() {
             let previous_value = *self.frec_data.get(
                 cmp::max(solution[i - 1], solution[i]),
@@ -163,7 +166,7 @@ impl FreqMatrix {
         }
     }
 
-    pub fn get_solution_freq_cost(&self, solution: &Vec<usize>) -> f64 {
+    pub fn get_solution_freq_cost(&self, solution: &[usize]) -> f64 {
         let mut total_cost: f64 = 0.;
 
         total_cost += self.get_edge_freq_cost(solution[0], 0);
@@ -178,9 +181,22 @@ impl FreqMatrix {
 
         total_cost
     }
+
     pub fn get_edge_freq_cost(&self, line: usize, column: usize) -> f64 {
-        let related_cost = *self.frec_data.get(line, column) as f64 / self.max_frec as f64;
+        *self.frec_data.get(line, column) as f64 / self.max_frec as f64
+    }
+
+    // Frecuencias por filas, para los puntos de control.
+    pub fn counts(&self) -> &[usize] {
+        &self.frec_data.data
+    }
 
-        related_cost
+    pub fn restore_counts(&mut self, counts: &[usize]) -> Result<(), String> {
+        if counts.len() != self.frec_data.data.len() {
+            return Err(format!("se esperaban {} frecuencias y hay {}", self.frec_data.data.len(), counts.len()));
+        }
+        self.frec_data.data.copy_from_slice(counts);
+        self.max_frec = counts.iter().cloned().fold(1, cmp::max);
+        Ok(())
     }
 }
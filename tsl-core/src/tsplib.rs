@@ -0,0 +1,413 @@
+use std::*;
+use std::collections::HashMap;
+
+use crate::coordinates;
+use crate::coordinates::DistanceFunction;
+use crate::error;
+use crate::error::LoadError;
+use crate::triangular::TriangularMatrix;
+
+// Instancias en formato TSPLIB. El nodo 1 de TSPLIB es el nodo 0 (origen) de
+// las soluciones.
+struct Keyword {
+    value: String,
+    line: usize,
+}
+
+struct Section<'a> {
+    line: usize,
+    tokens: Vec<(usize, usize, &'a str)>,
+}
+
+// Palabras clave y secciones de un fichero TSPLIB.
+struct Specification<'a> {
+    file_name: &'a str,
+    keywords: HashMap<String, Keyword>,
+    sections: HashMap<String, Section<'a>>,
+}
+
+impl<'a> Specification<'a> {
+    fn parse(file_name: &'a str, file_content: &'a str) -> Result<Specification<'a>, LoadError> {
+        let mut keywords = HashMap::new();
+        let mut sections = HashMap::new();
+        let mut lines = file_content.lines().enumerate().peekable();
+        while let Some((number, line)) = lines.next() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            let (key, value) = match line.find(':') {
+                Some(colon) => (line[..colon].trim(), line[colon + 1..].trim()),
+                None => (line, ""),
+            };
+
+            match key {
+                "EOF" => break,
+                "EDGE_WEIGHT_SECTION" | "NODE_COORD_SECTION" | "TOUR_SECTION" => {
+                    let tokens = read_section(&mut lines);
+                    sections.insert(key.to_string(), Section { line: number + 1, tokens });
+                },
+                // Secciones que no afectan a las distancias.
+                "DISPLAY_DATA_SECTION" | "FIXED_EDGES_SECTION" => {
+                    read_section(&mut lines);
+                },
+                _ if key.ends_with("_SECTION") =>
+                    return Err(format_error(file_name, number + 1, format!("seccion no soportada: {}", key))),
+                _ => {
+                    keywords.insert(key.to_string(), Keyword { value: value.to_string(), line: number + 1 });
+                },
+            }
+        }
+
+        Ok(Specification { file_name, keywords, sections })
+    }
+
+    fn keyword(&self, key: &str) -> Result<&Keyword, LoadError> {
+        self.keywords.get(key)
+            .ok_or_else(|| format_error(self.file_name, 0, format!("falta la palabra clave {}", key)))
+    }
+
+    fn section(&self, key: &str) -> Result<&Section<'a>, LoadError> {
+        self.sections.get(key)
+            .ok_or_else(|| format_error(self.file_name, 0, format!("falta la seccion {}", key)))
+    }
+
+    fn dimension(&self) -> Result<usize, LoadError> {
+        let dimension = self.keyword("DIMENSION")?;
+        dimension.value.parse::<usize>()
+            .ok()
+            .filter(|&n| n > 1)
+            .ok_or_else(|| format_error(self.file_name, dimension.line,
+                                        format!("DIMENSION no valida: {}", dimension.value)))
+    }
+
+    fn check_type(&self, expected: &str) -> Result<(), LoadError> {
+        match self.keywords.get("TYPE") {
+            Some(problem_type) if problem_type.value != expected =>
+                Err(format_error(self.file_name, problem_type.line,
+                                 format!("tipo de fichero no soportado: {}", problem_type.value))),
+            _ => Ok(()),
+        }
+    }
+}
+
+// Un fichero TSPLIB empieza por una palabra clave; el formato triangular propio
+// empieza directamente por numeros.
+pub fn is_tsplib(file_content: &str) -> bool {
+    file_content.trim_start()
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic())
+}
+
+pub fn from_file(file_name: &str) -> Result<TriangularMatrix<usize>, LoadError> {
+    let file_content = error::read_file(file_name)?;
+    parse(file_name, &file_content)
+}
+
+pub fn parse(file_name: &str, file_content: &str) -> Result<TriangularMatrix<usize>, LoadError> {
+    let specification = Specification::parse(file_name, file_content)?;
+    let keyword = |key: &str| specification.keyword(key);
+    let section = |key: &str| specification.section(key);
+
+    specification.check_type("TSP")?;
+    let number_of_nodes = specification.dimension()?;
+
+    let edge_weight_type = keyword("EDGE_WEIGHT_TYPE")?;
+    if edge_weight_type.value == "EXPLICIT" {
+        return explicit_weights(file_name, number_of_nodes,
+                                keyword("EDGE_WEIGHT_FORMAT")?, section("EDGE_WEIGHT_SECTION")?);
+    }
+    match DistanceFunction::from_name(&edge_weight_type.value) {
+        Some(distance) => {
+            let coordinates = node_coordinates(file_name, number_of_nodes, section("NODE_COORD_SECTION")?)?;
+            Ok(coordinates::cost_map(&coordinates, distance))
+        },
+        None => Err(format_error(file_name, edge_weight_type.line,
+                                 format!("EDGE_WEIGHT_TYPE no soportado: {}", edge_weight_type.value))),
+    }
+}
+
+fn explicit_weights(file_name: &str, number_of_nodes: usize, edge_weight_format: &Keyword, section: &Section)
+                    -> Result<TriangularMatrix<usize>, LoadError> {
+    // Posiciones (fila, columna) de la matriz completa en el orden del fichero.
+    let n = number_of_nodes;
+    let positions: Vec<(usize, usize)> = match edge_weight_format.value.as_str() {
+        "FULL_MATRIX" => (0..n).flat_map(|i| (0..n).map(move |j| (i, j))).collect(),
+        "LOWER_ROW" => (1..n).flat_map(|i| (0..i).map(move |j| (i, j))).collect(),
+        "LOWER_DIAG_ROW" => (0..n).flat_map(|i| (0..=i).map(move |j| (i, j))).collect(),
+        "UPPER_ROW" => (0..n).flat_map(|i| (i + 1..n).map(move |j| (i, j))).collect(),
+        "UPPER_DIAG_ROW" => (0..n).flat_map(|i| (i..n).map(move |j| (i, j))).collect(),
+        _ => return Err(format_error(file_name, edge_weight_format.line,
+                                     format!("EDGE_WEIGHT_FORMAT no soportado: {}", edge_weight_format.value))),
+    };
+    if section.tokens.len() != positions.len() {
+        return Err(format_error(file_name, section.line,
+                                format!("se esperaban {} pesos en EDGE_WEIGHT_SECTION y hay {}",
+                                        positions.len(), section.tokens.len())));
+    }
+
+    let mut cost_map = TriangularMatrix::<usize>::filled_zeros(number_of_nodes);
+    for (&(i, j), &(line, column, token)) in positions.iter().zip(section.tokens.iter()) {
+        let weight = error::parse_token::<usize>(file_name, line, column, token)?;
+        if i > j {
+            cost_map.set(i, j, weight);
+        } else if i < j && edge_weight_format.value != "FULL_MATRIX" {
+            cost_map.set(j, i, weight);
+        }
+    }
+
+    Ok(cost_map)
+}
+
+// Recorrido completo, con el origen, tal y como aparece en TOUR_SECTION. Los
+// nodos pueden estar numerados desde 1 (TSPLIB) o desde 0 (origen incluido).
+pub fn read_tour(file_name: &str) -> Result<Vec<usize>, LoadError> {
+    let file_content = error::read_file(file_name)?;
+    let specification = Specification::parse(file_name, &file_content)?;
+    specification.check_type("TOUR")?;
+    let section = specification.section("TOUR_SECTION")?;
+
+    let mut nodes = Vec::new();
+    for &(line, column, token) in section.tokens.iter() {
+        if token == "-1" {
+            break;
+        }
+        nodes.push(error::parse_token::<usize>(file_name, line, column, token)?);
+    }
+    if !nodes.contains(&0) {
+        nodes.iter_mut().for_each(|node| *node = node.saturating_sub(1));
+    }
+
+    let mut visited = vec![false; nodes.len()];
+    for &node in nodes.iter() {
+        if node >= nodes.len() || visited[node] {
+            return Err(format_error(file_name, section.line,
+                                    format!("el recorrido no visita cada nodo una vez: {}", node)));
+        }
+        visited[node] = true;
+    }
+    if let Ok(dimension) = specification.keyword("DIMENSION") {
+        if dimension.value != nodes.len().to_string() {
+            return Err(format_error(file_name, dimension.line,
+                                    format!("DIMENSION es {} y el recorrido tiene {} nodos",
+                                            dimension.value, nodes.len())));
+        }
+    }
+
+    Ok(nodes)
+}
+
+pub fn write_tour(file_name: &str, name: &str, tour: &[usize], cost: f64, one_based: bool) -> io::Result<()> {
+    let first_node = if one_based { 1 } else { 0 };
+    let mut file_content = format!("NAME : {}\nCOMMENT : Coste {}\nTYPE : TOUR\nDIMENSION : {}\nTOUR_SECTION\n",
+                                   name, cost, tour.len());
+    for node in tour.iter() {
+        file_content += &format!("{}\n", node + first_node);
+    }
+    file_content += "-1\nEOF\n";
+    fs::write(file_name, file_content)
+}
+
+// Cada nodo aparece como "id x y", con los id de 1 a DIMENSION en cualquier orden.
+fn node_coordinates(file_name: &str, number_of_nodes: usize, section: &Section)
+                    -> Result<Vec<(f64, f64)>, LoadError> {
+    if section.tokens.len() != 3 * number_of_nodes {
+        return Err(format_error(file_name, section.line,
+                                format!("se esperaban {} nodos en NODE_COORD_SECTION y hay {} valores",
+                                        number_of_nodes, section.tokens.len())));
+    }
+
+    let mut coordinates = vec![None; number_of_nodes];
+    for node in section.tokens.chunks(3) {
+        let (line, column, token) = node[0];
+        let id = error::parse_token::<usize>(file_name, line, column, token)?;
+        if id == 0 || id > number_of_nodes || coordinates[id - 1].is_some() {
+            return Err(format_error(file_name, line, format!("nodo no valido o repetido: {}", id)));
+        }
+        let x = error::parse_token::<f64>(file_name, node[1].0, node[1].1, node[1].2)?;
+        let y = error::parse_token::<f64>(file_name, node[2].0, node[2].1, node[2].2)?;
+        coordinates[id - 1] = Some((x, y));
+    }
+
+    // Con tantos valores como nodos y sin repetidos, estan todos.
+    Ok(coordinates.into_iter().flatten().collect())
+}
+
+fn format_error(file_name: &str, line: usize, message: String) -> LoadError {
+    LoadError::Format { file_name: file_name.to_string(), line, message }
+}
+
+// Valores de una seccion hasta la siguiente palabra clave.
+fn read_section<'a, I>(lines: &mut iter::Peekable<I>) -> Vec<(usize, usize, &'a str)>
+    where I: Iterator<Item = (usize, &'a str)>
+{
+    let mut tokens = Vec::new();
+    while let Some(&(number, line)) = lines.peek() {
+        if is_tsplib(line) {
+            break;
+        }
+        tokens.extend(error::tokens(line).map(|(column, token)| (number + 1, column, token)));
+        lines.next();
+    }
+    tokens
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tour;
+
+    // burma14 de TSPLIB, con distancias GEO; su recorrido optimo mide 3323.
+    const BURMA14: &str = "\
+NAME: burma14
+TYPE: TSP
+DIMENSION: 14
+EDGE_WEIGHT_TYPE: GEO
+NODE_COORD_SECTION
+   1  16.47       96.10
+   2  16.47       94.44
+   3  20.09       92.54
+   4  22.39       93.37
+   5  25.23       97.24
+   6  22.00       96.05
+   7  20.47       97.02
+   8  17.20       96.29
+   9  16.30       97.38
+  10  14.05       98.12
+  11  16.53       97.38
+  12  21.52       95.59
+  13  19.41       97.13
+  14  20.09       94.55
+EOF
+";
+
+    fn distances(cost_map: &TriangularMatrix<usize>) -> Vec<Vec<usize>> {
+        let nodes = cost_map.number_of_lines;
+        (0..nodes).map(|i| (0..nodes).map(|j| tour::distance(cost_map, i, j)).collect()).collect()
+    }
+
+    fn explicit(format: &str, weights: &str) -> String {
+        format!("NAME: prueba\nTYPE: TSP\nDIMENSION: 4\nEDGE_WEIGHT_TYPE: EXPLICIT\n\
+                 EDGE_WEIGHT_FORMAT: {}\nEDGE_WEIGHT_SECTION\n{}EOF\n", format, weights)
+    }
+
+    fn error(file_content: &str) -> String {
+        parse("prueba.tsp", file_content).err().expect("se esperaba un error").to_string()
+    }
+
+    // Fichero temporal propio de cada test, para que puedan ejecutarse a la vez.
+    fn temporary_file(name: &str) -> String {
+        env::temp_dir().join(format!("tsl_{}_{}", process::id(), name)).to_str().unwrap().to_string()
+    }
+
+    fn read_tour_error(name: &str, file_content: &str) -> (String, String) {
+        let file_name = temporary_file(name);
+        fs::write(&file_name, file_content).unwrap();
+        let tour = read_tour(&file_name);
+        fs::remove_file(&file_name).unwrap();
+        (file_name, tour.expect_err("se esperaba un error").to_string())
+    }
+
+    #[test]
+    fn every_edge_weight_format_gives_the_same_matrix() {
+        let expected = vec![
+            vec![0, 3, 5, 7],
+            vec![3, 0, 4, 6],
+            vec![5, 4, 0, 2],
+            vec![7, 6, 2, 0],
+        ];
+        let formats = [
+            ("FULL_MATRIX", "0 3 5 7\n3 0 4 6\n5 4 0 2\n7 6 2 0\n"),
+            ("LOWER_ROW", "3\n5 4\n7 6 2\n"),
+            ("LOWER_DIAG_ROW", "0\n3 0\n5 4 0\n7 6 2 0\n"),
+            ("UPPER_ROW", "3 5 7\n4 6\n2\n"),
+            ("UPPER_DIAG_ROW", "0 3 5 7\n0 4 6\n0 2\n0\n"),
+        ];
+        for (format, weights) in formats {
+            let cost_map = parse("prueba.tsp", &explicit(format, weights)).unwrap();
+            assert_eq!(distances(&cost_map), expected, "{}", format);
+        }
+        // Los pesos no tienen por que seguir las filas de la matriz.
+        let cost_map = parse("prueba.tsp", &explicit("UPPER_ROW", "3 5 7 4 6 2\n")).unwrap();
+        assert_eq!(distances(&cost_map), expected);
+    }
+
+    #[test]
+    fn geo_distances_of_burma14() {
+        let cost_map = parse("burma14.tsp", BURMA14).unwrap();
+        assert_eq!(cost_map.number_of_lines, 14);
+        assert_eq!(distances(&cost_map)[0][1..4], [153, 510, 706]);
+        let optimal_tour = [1, 13, 2, 3, 4, 5, 11, 6, 12, 7, 10, 8, 9];
+        assert_eq!(tour::calculate_cost(&cost_map, &optimal_tour), 3323.0);
+    }
+
+    #[test]
+    fn node_coordinates_in_any_order() {
+        let file_content = "TYPE: TSP\nDIMENSION: 3\nEDGE_WEIGHT_TYPE: EUC_2D\n\
+                            NODE_COORD_SECTION\n3 0 4\n1 0 0\n2 3 0\nEOF\n";
+        let cost_map = parse("prueba.tsp", file_content).unwrap();
+        assert_eq!(distances(&cost_map), vec![vec![0, 3, 4], vec![3, 0, 5], vec![4, 5, 0]]);
+    }
+
+    #[test]
+    fn malformed_instances() {
+        assert_eq!(error(&explicit("LOWER_ROW", "3\n5 x\n7 6 2\n")), "prueba.tsp:8:3: valor no valido: x");
+        assert_eq!(error(&explicit("LOWER_ROW", "3\n5 4\n7 6\n")),
+                   "prueba.tsp:6: se esperaban 6 pesos en EDGE_WEIGHT_SECTION y hay 5");
+        assert_eq!(error(&explicit("LOWER_COL", "3\n5 4\n7 6 2\n")),
+                   "prueba.tsp:5: EDGE_WEIGHT_FORMAT no soportado: LOWER_COL");
+        assert_eq!(error("TYPE: TSP\nEDGE_WEIGHT_TYPE: EUC_2D\nNODE_COORD_SECTION\n1 0 0\n2 1 1\n"),
+                   "prueba.tsp: falta la palabra clave DIMENSION");
+        assert_eq!(error("TYPE: TSP\nDIMENSION: 1\n"), "prueba.tsp:2: DIMENSION no valida: 1");
+        assert_eq!(error("TYPE: ATSP\nDIMENSION: 3\n"), "prueba.tsp:1: tipo de fichero no soportado: ATSP");
+        assert_eq!(error("TYPE: TSP\nDIMENSION: 2\nEDGE_WEIGHT_TYPE: MAN_3D\n"),
+                   "prueba.tsp:3: EDGE_WEIGHT_TYPE no soportado: MAN_3D");
+        assert_eq!(error("TYPE: TSP\nDIMENSION: 2\nEDGE_WEIGHT_TYPE: EUC_2D\n"),
+                   "prueba.tsp: falta la seccion NODE_COORD_SECTION");
+        assert_eq!(error("TYPE: TSP\nDIMENSION: 2\nDEPOT_SECTION\n1\n-1\n"),
+                   "prueba.tsp:3: seccion no soportada: DEPOT_SECTION");
+    }
+
+    #[test]
+    fn malformed_node_coordinates() {
+        let coordinates = |section: &str| format!("TYPE: TSP\nDIMENSION: 2\nEDGE_WEIGHT_TYPE: EUC_2D\n\
+                                                   NODE_COORD_SECTION\n{}EOF\n", section);
+        assert_eq!(error(&coordinates("1 0 0\n1 3 4\n")), "prueba.tsp:6: nodo no valido o repetido: 1");
+        assert_eq!(error(&coordinates("1 0 0\n3 3 4\n")), "prueba.tsp:6: nodo no valido o repetido: 3");
+        assert_eq!(error(&coordinates("1 0 0\n2 3 y\n")), "prueba.tsp:6:5: valor no valido: y");
+        assert_eq!(error(&coordinates("1 0 0\n2 3\n")),
+                   "prueba.tsp:4: se esperaban 2 nodos en NODE_COORD_SECTION y hay 5 valores");
+    }
+
+    #[test]
+    fn tours_written_are_read_back() {
+        let tour = vec![0, 3, 1, 4, 2];
+        for (one_based, first_line) in [(false, "0"), (true, "1")] {
+            let file_name = temporary_file(&format!("ida_y_vuelta_{}.tour", one_based));
+            write_tour(&file_name, "prueba", &tour, 42.0, one_based).unwrap();
+            let file_content = fs::read_to_string(&file_name).unwrap();
+            let read = read_tour(&file_name);
+            fs::remove_file(&file_name).unwrap();
+            assert!(file_content.contains(&format!("DIMENSION : 5\nTOUR_SECTION\n{}\n", first_line)),
+                    "{}", file_content);
+            assert_eq!(read.unwrap(), tour);
+        }
+    }
+
+    #[test]
+    fn tours_that_are_not_permutations() {
+        let tour_file = |section: &str| format!("TYPE : TOUR\nDIMENSION : 3\nTOUR_SECTION\n{}-1\nEOF\n", section);
+        let cases = [
+            ("repetido.tour", tour_file("1\n2\n2\n"), "3: el recorrido no visita cada nodo una vez: 1"),
+            ("fuera.tour", tour_file("0\n1\n3\n"), "3: el recorrido no visita cada nodo una vez: 3"),
+            ("dimension.tour", tour_file("1\n2\n3\n4\n"), "2: DIMENSION es 3 y el recorrido tiene 4 nodos"),
+            ("valor.tour", tour_file("1\n2\n3x\n"), "6:1: valor no valido: 3x"),
+            ("tipo.tour", "TYPE : TSP\nTOUR_SECTION\n1\n-1\n".to_string(), "1: tipo de fichero no soportado: TSP"),
+        ];
+        for (name, file_content, expected) in cases {
+            let (file_name, error) = read_tour_error(name, &file_content);
+            assert_eq!(error, format!("{}:{}", file_name, expected));
+        }
+    }
+}